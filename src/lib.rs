@@ -1,2 +1,6 @@
 pub mod mine_sweeper_board;
+pub mod mine_sweeper_builder;
+pub mod mine_sweeper_driver;
+pub mod mine_sweeper_error;
 pub mod mine_sweeper_impl;
+pub mod mine_sweeper_solver;
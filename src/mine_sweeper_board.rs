@@ -1,14 +1,42 @@
 //! This module contains the components that make up a game of minesweeper played on a 2D grid
 //!
 
+use serde::{Deserialize, Serialize};
+
 // default characters printing game cells to standard output
 pub const MINE: char = '\u{25CF}'; // UTF-8 black circle \u{25CF}
-pub const REVEALED: char = '0'; // UTF-8 ballot box \u{2610}
+pub const REVEALED: char = ' '; // blank, revealed cells with no adjacent mines are left empty
 pub const HIDDEN: char = '\u{25A1}'; // UTF-8 white square
 pub const QUESTION: char = '\u{003F}'; // question mark
 pub const FLAG: char = '⚑'; // UTF-8 black flag \u{2691}
+pub const XRAY: char = '\u{25CC}'; // UTF-8 dotted circle, marks hidden mines while x-ray is on
+
+/// the set of characters used to draw the cells of a grid
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Theme {
+    pub name: String,
+    pub mine: char,
+    pub revealed: char,
+    pub hidden: char,
+    pub question: char,
+    pub flag: char,
+}
+
+impl Default for Theme {
+    /// the classic theme draws cells using the default characters defined in this module
+    fn default() -> Self {
+        Theme {
+            name: "classic".to_string(),
+            mine: MINE,
+            revealed: REVEALED,
+            hidden: HIDDEN,
+            question: QUESTION,
+            flag: FLAG,
+        }
+    }
+}
 
-#[derive(PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 /// holds information on the current state of a MineSweeper cell
 /// `Revealed` - a user has revealed the cell
 /// `Marked` - a user has "marked" a cell with either a Flag or Question Mark
@@ -19,20 +47,101 @@ pub enum CellState {
     Hidden,
 }
 
-#[derive(PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 /// holds information about whether or not a Cell is currently "marked" with a Flag, or  Question mark
 pub enum CellMarker {
     Flagged,
     Questioned,
 }
 
-#[derive(PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 /// the "kind" of cell, either the Cell is mined, or it is empty
 pub enum CellKind {
     Mine,
     Empty,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// how mines are spread across a grid when it is generated
+/// `Uniform` - every cell is equally likely to hold a mine
+/// `Clustered` - mines are grown in blobs, leaving larger open areas between them
+pub enum MineDistribution {
+    Uniform,
+    Clustered,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// what a player must do to win a game
+/// `FlagAllMines` - every mine must be flagged
+/// `RevealAllSafe` - every empty cell must be revealed, flags are optional
+pub enum WinCondition {
+    FlagAllMines,
+    RevealAllSafe,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// the classic difficulty levels, each a fixed board size and mine count
+/// `Beginner` - 9 rows by 9 columns holding 10 mines
+/// `Intermediate` - 16 rows by 16 columns holding 40 mines
+/// `Expert` - 16 rows by 30 columns holding 99 mines
+pub enum Difficulty {
+    Beginner,
+    Intermediate,
+    Expert,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// the state of a game
+/// `InProgress` - the game has been neither won nor lost yet
+/// `Won` - the win condition has been met
+/// `Lost` - a mine has been revealed
+pub enum GameState {
+    InProgress,
+    Won,
+    Lost,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// the outcome of a reveal
+/// `Mine` - the cell was an unrevealed mine, and the game is lost
+/// `Opening { newly_revealed }` - the cell had no adjacent mines, and the reveal opened
+/// `newly_revealed` cells, counting the cell itself
+/// `Number { count, newly_revealed }` - the cell has `count` adjacent mines, and the reveal opened
+/// `newly_revealed` cells, counting the cell itself
+/// `AlreadyRevealed` - the cell was revealed before, so nothing happened
+/// `Protected` - the cell holds a protected question mark and was left hidden
+pub enum RevealOutcome {
+    Mine,
+    Opening { newly_revealed: usize },
+    Number { count: u8, newly_revealed: usize },
+    AlreadyRevealed,
+    Protected,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// how the flags around a revealed number compare to its adjacent mine count
+/// `Satisfied` - there are as many adjacent flags as adjacent mines
+/// `Unsatisfied` - there are fewer adjacent flags than adjacent mines
+/// `Overflagged` - there are more adjacent flags than adjacent mines
+pub enum NumberStatus {
+    Satisfied,
+    Unsatisfied,
+    Overflagged,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// a rotation or reflection of a grid
+/// `Rotate90`, `Rotate180`, `Rotate270` - rotate the grid clockwise by the given degrees
+/// `FlipH` - mirror the grid left to right
+/// `FlipV` - mirror the grid top to bottom
+pub enum Transform {
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    FlipH,
+    FlipV,
+}
+
 /// The Basic "building-block" of a game of MineSweeper is a cell. Cells can have a mine in them, or
 /// be empty. Additionally, they can also be "marked" with a flag or a question mark. If a cell is
 /// next to one or more mines than the cell's adj_mine_count field will contain a count of the
@@ -85,8 +194,13 @@ pub trait MineSweeperGame {
     /// returns a count of the total number of mines in the grid
     fn total_mines(&self) -> usize;
 
-    /// reveals the cell at row index `r` and column index `c`
-    fn reveal_cell(&mut self, r: usize, c: usize);
+    /// reveals the cell at row index `r` and column index `c`, and returns what the reveal did
+    fn reveal_cell(&mut self, r: usize, c: usize) -> RevealOutcome;
+
+    /// reveals the cell at row index `r` and column index `c` as the first move of a game. Before
+    /// the game has started, any mines in the cell and its neighbors are moved elsewhere first,
+    /// so the first reveal is never a mine. Once the game has started, this is `reveal_cell`
+    fn first_reveal(&mut self, r: usize, c: usize) -> RevealOutcome;
 
     /// reveals all "lone" cells that are connected to the cell at index `r`,`c`
     /// A lone cell is a `CellKind::Empty` cell with an `adjacent mine count = 0`.
@@ -105,6 +219,10 @@ pub trait MineSweeperGame {
     /// will remove it. If the cell is unmarked, then the CellMarker given by `mark` is placed
     fn toggle_mark(&mut self, r: usize, c: usize, mark: CellMarker);
 
+    /// "chords" the revealed cell at index: r,c. If the number of flags adjacent to the cell equals
+    /// its adjacent mine count, then every adjacent cell that is not flagged is revealed
+    fn chord_cell(&mut self, r: usize, c: usize);
+
     /// returns `true` if the current game is won. A minesweeper game is won when all mined cells
     /// have been correctly flagged
     fn is_game_won(&self) -> bool;
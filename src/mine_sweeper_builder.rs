@@ -0,0 +1,364 @@
+//! This module contains a builder that configures every option of a new minesweeper `Grid`
+//! in one place, while `MineSweeperGame::init` stays the simple way to start a game.
+
+use crate::mine_sweeper_board::{Difficulty, Theme, WinCondition};
+use crate::mine_sweeper_impl::{Cell, Grid};
+use rand::{thread_rng, Rng};
+use std::fmt;
+use std::fmt::Formatter;
+
+/// the default for the largest number of rows or columns a built grid may have
+const DEFAULT_MAX_DIMENSION: usize = 1000;
+
+/// the errors that can occur when building a grid from a `GameBuilder`
+#[derive(Debug, Clone, PartialEq)]
+pub enum InitError {
+    /// the grid would have no cells, because `rows` or `cols` is zero
+    ZeroDimension { rows: usize, cols: usize },
+    /// the grid has room for `cells` cells, too few to hold `mines` mines
+    TooManyMines { mines: usize, cells: usize },
+    /// the mine density is not a fraction between 0 and 1
+    InvalidDensity(f32),
+    /// `rows` or `cols` is larger than `max_dimension`, or the grid has too many cells to count
+    TooLarge {
+        rows: usize,
+        cols: usize,
+        max_dimension: usize,
+    },
+}
+
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            InitError::ZeroDimension { rows, cols } => {
+                write!(f, "a {}x{} grid does not have any cells", rows, cols)
+            }
+            InitError::TooManyMines { mines, cells } => {
+                write!(f, "{} mines do not fit in a grid of {} cells", mines, cells)
+            }
+            InitError::InvalidDensity(density) => {
+                write!(f, "the density {} is not between 0 and 1", density)
+            }
+            InitError::TooLarge {
+                rows,
+                cols,
+                max_dimension,
+            } => write!(
+                f,
+                "a {}x{} grid is too large, rows and columns are limited to {}",
+                rows, cols, max_dimension
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InitError {}
+
+/// how many mines the builder places, the last setter called wins
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MineAmount {
+    Count(usize),
+    Density(f32),
+}
+
+/// configures a new `Grid<Cell>` through chainable setters, then builds it with `build`.
+/// A builder starts out as a beginner game with the default rules
+#[derive(Debug, Clone)]
+pub struct GameBuilder {
+    rows: usize,
+    cols: usize,
+    mines: MineAmount,
+    seed: Option<u64>,
+    win_condition: WinCondition,
+    questions_enabled: bool,
+    flags_enabled: bool,
+    auto_chord: bool,
+    training_mode: bool,
+    theme: Theme,
+    max_dimension: usize,
+}
+
+impl Default for GameBuilder {
+    fn default() -> Self {
+        GameBuilder {
+            rows: 9,
+            cols: 9,
+            mines: MineAmount::Count(10),
+            seed: None,
+            win_condition: WinCondition::FlagAllMines,
+            questions_enabled: true,
+            flags_enabled: true,
+            auto_chord: false,
+            training_mode: false,
+            theme: Theme::default(),
+            max_dimension: DEFAULT_MAX_DIMENSION,
+        }
+    }
+}
+
+impl GameBuilder {
+    /// returns a builder for a beginner game with the default rules
+    pub fn new() -> Self {
+        GameBuilder::default()
+    }
+
+    /// sets the board size and mine count to those of the classic `difficulty` level
+    pub fn difficulty(mut self, difficulty: Difficulty) -> Self {
+        let (rows, cols, mines) = match difficulty {
+            Difficulty::Beginner => (9, 9, 10),
+            Difficulty::Intermediate => (16, 16, 40),
+            Difficulty::Expert => (16, 30, 99),
+        };
+        self.rows = rows;
+        self.cols = cols;
+        self.mines = MineAmount::Count(mines);
+        self
+    }
+
+    /// sets the number of rows and columns of the grid
+    pub fn size(mut self, rows: usize, cols: usize) -> Self {
+        self.rows = rows;
+        self.cols = cols;
+        self
+    }
+
+    /// places exactly `mines` mines on the grid
+    pub fn mines(mut self, mines: usize) -> Self {
+        self.mines = MineAmount::Count(mines);
+        self
+    }
+
+    /// places mines on the given fraction of the cells, see `Grid::mines_for_density`
+    pub fn density(mut self, density: f32) -> Self {
+        self.mines = MineAmount::Density(density);
+        self
+    }
+
+    /// places the mines from `seed`, so that the same configuration always builds the same
+    /// grid. Without a seed, a random one is picked
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// sets what a player must do to win the game
+    pub fn win_condition(mut self, win_condition: WinCondition) -> Self {
+        self.win_condition = win_condition;
+        self
+    }
+
+    /// allows or forbids question marks
+    pub fn questions_enabled(mut self, enabled: bool) -> Self {
+        self.questions_enabled = enabled;
+        self
+    }
+
+    /// allows or forbids all markers. Without them the game is won by `RevealAllSafe`
+    pub fn flags_enabled(mut self, enabled: bool) -> Self {
+        self.flags_enabled = enabled;
+        self
+    }
+
+    /// turns on or off the automatic chording of satisfied numbers after a reveal
+    pub fn auto_chord(mut self, auto_chord: bool) -> Self {
+        self.auto_chord = auto_chord;
+        self
+    }
+
+    /// turns on or off training mode, where misplaced flags are removed instead of detonating
+    pub fn training_mode(mut self, training_mode: bool) -> Self {
+        self.training_mode = training_mode;
+        self
+    }
+
+    /// sets the characters used to draw the grid
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// sets the largest number of rows or columns the grid may have, larger grids are rejected
+    /// rather than allocating an enormous grid. Defaults to 1000
+    pub fn max_dimension(mut self, max_dimension: usize) -> Self {
+        self.max_dimension = max_dimension;
+        self
+    }
+
+    /// builds a new grid from the configured options. Returns an `InitError` if the grid would
+    /// have no cells, would be larger than `max_dimension`, or the mines cannot fit in it
+    pub fn build(&self) -> Result<Grid<Cell>, InitError> {
+        let (rows, cols) = (self.rows, self.cols);
+        if rows == 0 || cols == 0 {
+            return Err(InitError::ZeroDimension { rows, cols });
+        }
+        let too_large = InitError::TooLarge {
+            rows,
+            cols,
+            max_dimension: self.max_dimension,
+        };
+        if rows > self.max_dimension || cols > self.max_dimension {
+            return Err(too_large);
+        }
+        let cells = rows.checked_mul(cols).ok_or(too_large)?;
+        let mines = match self.mines {
+            MineAmount::Count(mines) => mines,
+            MineAmount::Density(density) if (0.0..=1.0).contains(&density) => {
+                Grid::mines_for_density(rows, cols, density)
+            }
+            MineAmount::Density(density) => return Err(InitError::InvalidDensity(density)),
+        };
+        if mines > cells {
+            return Err(InitError::TooManyMines { mines, cells });
+        }
+
+        let seed = self.seed.unwrap_or_else(|| thread_rng().gen());
+        let mut grid = Grid::init_seeded(rows, cols, mines, seed);
+        // the win condition is set first, disabling flags overrides it with `RevealAllSafe`
+        grid.set_win_condition(self.win_condition);
+        grid.set_flags_enabled(self.flags_enabled);
+        grid.set_questions_enabled(self.questions_enabled);
+        grid.set_auto_chord(self.auto_chord);
+        grid.set_training_mode(self.training_mode);
+        grid.set_theme(self.theme.clone());
+        Ok(grid)
+    }
+}
+
+impl Grid<Cell> {
+    /// returns a `GameBuilder` for configuring a new grid, see `GameBuilder::default`
+    pub fn builder() -> GameBuilder {
+        GameBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mine_sweeper_board::{Difficulty, MineSweeperGame, Theme, WinCondition};
+    use crate::mine_sweeper_builder::{GameBuilder, InitError};
+    use crate::mine_sweeper_impl::Grid;
+
+    #[test]
+    fn a_fully_customized_game_applies_every_option() {
+        let theme = Theme {
+            name: "ascii".to_string(),
+            mine: '*',
+            revealed: '.',
+            hidden: '#',
+            question: '?',
+            flag: 'F',
+        };
+        let grid = GameBuilder::new()
+            .size(12, 10)
+            .density(0.25)
+            .seed(7)
+            .win_condition(WinCondition::RevealAllSafe)
+            .questions_enabled(false)
+            .auto_chord(true)
+            .training_mode(true)
+            .theme(theme.clone())
+            .build()
+            .unwrap();
+
+        assert_eq!(grid.dimensions(), (12, 10));
+        assert_eq!(grid.total_mines(), 30);
+        assert_eq!(grid.seed(), Some(7));
+        assert_eq!(grid.win_condition(), WinCondition::RevealAllSafe);
+        assert!(!grid.questions_enabled());
+        assert!(grid.flags_enabled());
+        assert!(grid.auto_chord());
+        assert!(grid.training_mode());
+        assert_eq!(grid.theme(), &theme);
+
+        let same_seed = Grid::builder()
+            .size(12, 10)
+            .density(0.25)
+            .seed(7)
+            .build()
+            .unwrap();
+        assert_eq!(same_seed.mine_indices(), grid.mine_indices());
+    }
+
+    #[test]
+    fn difficulty_sets_the_classic_sizes_and_mine_counts() {
+        let grid = Grid::builder().build().unwrap();
+        assert_eq!((grid.dimensions(), grid.total_mines()), ((9, 9), 10));
+        assert!(grid.seed().is_some());
+
+        let grid = Grid::builder()
+            .difficulty(Difficulty::Expert)
+            .build()
+            .unwrap();
+        assert_eq!((grid.dimensions(), grid.total_mines()), ((16, 30), 99));
+
+        // a density given after the difficulty replaces its mine count
+        let grid = Grid::builder()
+            .difficulty(Difficulty::Intermediate)
+            .density(0.15)
+            .build()
+            .unwrap();
+        assert_eq!(grid.total_mines(), Grid::mines_for_density(16, 16, 0.15));
+    }
+
+    #[test]
+    fn disabling_flags_forces_the_reveal_all_safe_win_condition() {
+        let grid = Grid::builder()
+            .win_condition(WinCondition::FlagAllMines)
+            .flags_enabled(false)
+            .build()
+            .unwrap();
+        assert!(!grid.flags_enabled());
+        assert_eq!(grid.win_condition(), WinCondition::RevealAllSafe);
+    }
+
+    #[test]
+    fn build_rejects_impossible_configurations() {
+        assert_eq!(
+            Grid::builder().size(0, 5).build().unwrap_err(),
+            InitError::ZeroDimension { rows: 0, cols: 5 }
+        );
+        assert_eq!(
+            Grid::builder().size(3, 3).mines(10).build().unwrap_err(),
+            InitError::TooManyMines {
+                mines: 10,
+                cells: 9
+            }
+        );
+        assert_eq!(
+            Grid::builder().density(1.5).build().unwrap_err(),
+            InitError::InvalidDensity(1.5)
+        );
+    }
+
+    #[test]
+    fn build_rejects_oversized_grids() {
+        assert_eq!(
+            Grid::builder().size(1001, 5).build().unwrap_err(),
+            InitError::TooLarge {
+                rows: 1001,
+                cols: 5,
+                max_dimension: 1000
+            }
+        );
+        assert_eq!(
+            Grid::builder()
+                .size(usize::MAX, 2)
+                .max_dimension(usize::MAX)
+                .build()
+                .unwrap_err(),
+            InitError::TooLarge {
+                rows: usize::MAX,
+                cols: 2,
+                max_dimension: usize::MAX
+            }
+        );
+        assert_eq!(
+            Grid::builder()
+                .size(20, 30)
+                .max_dimension(30)
+                .build()
+                .unwrap()
+                .dimensions(),
+            (20, 30)
+        );
+    }
+}
@@ -0,0 +1,1539 @@
+//! This module contains the `CommandLineDriver`, which enables a user to play a Mine Sweeper Game
+//! via the command line (stdin). It can be embedded in other programs, which can add their own
+//! commands with `CommandLineDriver::register_command`
+//!
+//! The user will enter commands using a space separated string in one of the following formats.
+//! Several commands can be entered on one line by separating them with a `;`, e.g. `r 0 0; f 1 1`
+//! * to create a new game with 5 rows and 5 columns: `n 5 5`
+//! * to create the same 5 by 5 game as someone else, from the seed they shared: `n 5 5 42`
+//! * to reveal the square at row 0 column 1: `r 0 1`
+//! * to flag a square at row 2 column 4: `f 2 4`
+//! * to place a question mark on a square at row 1 column 3: `q 1 3`
+//! * to chord the revealed square at row 1 column 1: `c 1 1`
+//! * to show information about the square at row 1 column 2 and its neighbors: `info 1 2`
+//! * to reveal a random square that does not contain a mine: `free`
+//! * to reveal the square least likely to contain a mine: `safe`
+//! * to move the cursor (drawn as `>`) around the board: `up`, `down`, `left`, `right`
+//! * to reveal or flag the square under the cursor: `reveal`, `flag`
+//! * to toggle chess notation, where squares are given as column letter and row number (`r B3`):
+//!   `notation`
+//! * to undo the last reveal (and the squares it opened): `undo-reveal`
+//! * to remove every flag and question mark, keeping the revealed squares: `clearmarks`
+//! * to toggle training mode (misplaced flags are removed instead of detonating): `training`
+//! * to list the moves that make sense right now: `moves`
+//! * to toggle x-ray, a practice aid that marks hidden mines (recorded in the game summary): `xray`
+//! * to show the rules and settings of the current game: `settings`
+//! * to redraw the board without making a move: `show`, or an empty line
+//! * to watch the current game played back from its first move: `replay`
+//! * to replace the game with a board built from a layout, where `*` is a mine, `.` is an empty
+//!   square and `/` separates the rows: `load-layout "*../..*"`
+//! * to replace the game with a board shared as a code: `load-code 3x3.BE`
+
+use crate::mine_sweeper_board::{
+    CellKind, CellMarker, CellState, MineSweeperCell, MineSweeperGame, RevealOutcome, WinCondition,
+};
+use crate::mine_sweeper_impl::{Cell, Grid};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::io::{BufRead, ErrorKind};
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// the default for the largest number of rows or columns a new game may have
+const DEFAULT_MAX_DIMENSION: usize = 1000;
+
+/// the default pause between the steps of a replay
+const DEFAULT_REPLAY_DELAY: Duration = Duration::from_millis(500);
+
+/// a command added to the driver by an embedder, it acts on the game and returns the message
+/// to show the user
+pub type CustomCommand<T> = Box<dyn Fn(&mut T) -> String>;
+
+pub struct CommandLineDriver<T: MineSweeperGame> {
+    pub game: T,
+    pub cursor: (usize, usize),
+    /// if set, the game is saved to this path when the input ends (Ctrl-D)
+    pub auto_save: Option<PathBuf>,
+    /// if set, squares are addressed in chess notation: a column letter followed by a row number
+    pub chess_notation: bool,
+    /// the largest number of rows or columns a new game may have, larger games are rejected
+    /// rather than allocating an enormous grid
+    pub max_dimension: usize,
+    /// if set, the game is lost once this much time has been played
+    pub time_limit: Option<Duration>,
+    /// returns the current instant, the time limit is checked against this clock
+    pub clock: fn() -> Instant,
+    /// the board and cursor position before the first move of the current game, if a move
+    /// has been made
+    pub start: Option<(Grid<Cell>, (usize, usize))>,
+    /// the moves made since `start`, in the order they were made
+    pub move_log: Vec<Command>,
+    /// the pause between the steps of a replay
+    pub replay_delay: Duration,
+    /// additional commands, keyed by the name that is entered to run them. A built-in command
+    /// with the same name takes precedence
+    pub custom_commands: HashMap<String, CustomCommand<T>>,
+    /// if set, the `debug` command prints the whole board, mines included. Embeddings that
+    /// should not spoil the game can turn it off
+    pub allow_debug: bool,
+    /// if set, flagging a square cycles its marker: flag, question mark, then cleared. Otherwise
+    /// flagging toggles a flag
+    pub flag_cycles: bool,
+    /// if set, the first reveal of a game is never a mine, any mines around it are moved
+    /// elsewhere first
+    pub safe_first_reveal: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Quit,
+    Debug,
+    New(usize, usize, Option<u64>),
+    Reveal(usize, usize),
+    Flag(usize, usize),
+    Question(usize, usize),
+    Chord(usize, usize),
+    Training,
+    UndoReveal,
+    ClearMarks,
+    Info(usize, usize),
+    Free,
+    Safe,
+    // the reveals made by `free` and `safe`, as recorded for a replay. They are played back
+    // exactly as the assist made them, without choosing a cell again
+    FreeReveal(usize, usize),
+    SafeReveal(usize, usize),
+    MoveCursor(isize, isize),
+    Notation,
+    Xray,
+    Moves,
+    Settings,
+    Show,
+    LoadLayout(String),
+    LoadCode(String),
+    Replay,
+    Custom(String),
+}
+
+impl Command {
+    /// returns `true` if the command is a move that changes the board or cursor and is recorded
+    /// as is for a replay. Free and safe reveals choose their own cell, so they are recorded as
+    /// the `FreeReveal` or `SafeReveal` they made instead
+    fn is_recorded(&self) -> bool {
+        matches!(
+            self,
+            Command::Reveal(..)
+                | Command::Flag(..)
+                | Command::Question(..)
+                | Command::Chord(..)
+                | Command::UndoReveal
+                | Command::ClearMarks
+                | Command::Training
+                | Command::Xray
+                | Command::MoveCursor(..)
+                | Command::Custom(..)
+        )
+    }
+}
+
+impl CommandLineDriver<Grid<Cell>> {
+    pub fn new(game: Grid<Cell>) -> Self {
+        CommandLineDriver {
+            game,
+            cursor: (0, 0),
+            auto_save: None,
+            chess_notation: false,
+            max_dimension: DEFAULT_MAX_DIMENSION,
+            time_limit: None,
+            clock: Instant::now,
+            start: None,
+            move_log: vec![],
+            replay_delay: DEFAULT_REPLAY_DELAY,
+            custom_commands: HashMap::new(),
+            allow_debug: true,
+            flag_cycles: false,
+            safe_first_reveal: true,
+        }
+    }
+
+    /// registers a custom command that is run by entering `name`, replacing any custom command
+    /// already registered under that name
+    pub fn register_command<F>(&mut self, name: &str, command: F)
+    where
+        F: Fn(&mut Grid<Cell>) -> String + 'static,
+    {
+        self.custom_commands
+            .insert(name.to_string(), Box::new(command));
+    }
+
+    /// starts a minesweeper game and waits for input from stdin
+    pub fn start(&mut self) {
+        let stdin = io::stdin();
+        self.run(&mut stdin.lock());
+    }
+
+    /// plays a minesweeper game reading commands from `input` until the game is over, the user
+    /// quits, or the input ends. If the input ends mid-game, the game is auto-saved (if enabled)
+    pub fn run<R: BufRead>(&mut self, input: &mut R) {
+        loop {
+            match CommandLineDriver::read_line(input) {
+                Ok(None) => {
+                    self.save_on_exit();
+                    break;
+                }
+                // each command of a batch is parsed just before it is applied, so that it sees
+                // the effects of the commands before it
+                Ok(Some(batch_str)) => {
+                    for command_str in split_batch(&batch_str) {
+                        match self.parse_command_line(command_str) {
+                            Ok(Command::Quit) => return,
+                            Ok(command) => {
+                                let output = self.execute(command);
+                                if !output.is_empty() {
+                                    println!("{}", output);
+                                }
+                            }
+                            Err(e) => {
+                                println!("{}", &e);
+                            }
+                        }
+                        if self.is_game_over() {
+                            return;
+                        }
+                    }
+                }
+                Err(e) => {
+                    println!("{}", e);
+                    break;
+                }
+            }
+            println!(
+                "mines: {}",
+                counter_display(self.game.mines_remaining() as isize)
+            );
+            println!("{}", self.render());
+        }
+    }
+
+    /// returns `true` if a time limit is set and the time played has gone past it. The timer
+    /// starts with the first reveal, so time can't run out before then
+    fn is_time_up(&self) -> bool {
+        self.time_limit
+            .is_some_and(|limit| self.game.elapsed_at((self.clock)()) > limit)
+    }
+
+    /// returns `true` if the game has been won or lost, after printing the outcome. A game that
+    /// has run out of time is lost first
+    fn is_game_over(&mut self) -> bool {
+        if self.is_time_up() && !self.game.is_game_lost() && !self.game.is_game_won() {
+            self.game.lose_on_time();
+        }
+        match self.outcome() {
+            Some(outcome) => {
+                println!("{}", outcome);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// returns the banner, summary and board to show once the game is over, or `None` while it
+    /// is still being played. A win also shows how to rebuild the board, so the player can
+    /// challenge friends to it: the `n` command with the seed if `init_with_seed` rebuilds it,
+    /// and the `load-code` command with the board code otherwise
+    fn outcome(&self) -> Option<String> {
+        let mut outcome = vec![];
+        if self.game.is_game_lost() {
+            if self.game.lost_on_time() {
+                outcome.push("time up".to_string());
+            } else {
+                outcome.push("you hit a mine!".to_string());
+            }
+            outcome.push(self.game.explain_loss());
+            let missed = self
+                .game
+                .unflagged_mines()
+                .iter()
+                .map(|(r, c)| format!("{} {}", r, c))
+                .collect::<Vec<String>>();
+            outcome.push(format!("unflagged mines: {}", missed.join(", ")));
+        } else if self.game.is_game_won() {
+            outcome.push("you win!!".to_string());
+            let (rows, cols) = self.game.dimensions();
+            match self.game.reproducible_seed() {
+                Some(seed) => outcome.push(format!(
+                    "beat my time on seed {}, {}x{}: n {} {} {}",
+                    seed, rows, cols, rows, cols, seed
+                )),
+                None => {
+                    let code = self.game.to_code();
+                    outcome.push(format!(
+                        "beat my time on board {}: load-code {}",
+                        code, code
+                    ))
+                }
+            }
+        } else {
+            return None;
+        }
+        outcome.push(self.game.summary().to_string());
+        outcome.push(format!("{:?}", self.game));
+        Some(outcome.join("\n"))
+    }
+
+    /// applies a parsed `Command` to the current game and returns any message that should be
+    /// shown to the user
+    fn execute(&mut self, command: Command) -> String {
+        // the board is remembered before the first move, so that the game can be replayed
+        let is_move = command.is_recorded() || matches!(command, Command::Free | Command::Safe);
+        if is_move && self.start.is_none() {
+            self.start = Some((self.game.clone(), self.cursor));
+        }
+        if command.is_recorded() {
+            self.move_log.push(command.clone());
+        }
+        match command {
+            Command::Quit => String::new(),
+            // the board is redrawn after every command, so there is nothing else to do
+            Command::Show => String::new(),
+            Command::LoadLayout(layout) => match Grid::from_layout(&layout) {
+                Ok(game) => {
+                    self.game = game;
+                    self.cursor = (0, 0);
+                    self.clear_moves();
+                    String::new()
+                }
+                Err(e) => format!("could not load the layout: {}", e),
+            },
+            Command::LoadCode(code) => match Grid::from_code(&code) {
+                Ok(game) => {
+                    self.game = game;
+                    self.cursor = (0, 0);
+                    self.clear_moves();
+                    String::new()
+                }
+                Err(e) => format!("could not load the board code: {}", e),
+            },
+            Command::Debug if self.allow_debug => format!("{:?}", &self.game),
+            Command::Debug => "disabled".to_string(),
+            Command::New(r, c, seed) => {
+                self.game = match seed {
+                    Some(seed) => Grid::init_with_seed(r, c, seed),
+                    None => Grid::init(r, c),
+                };
+                self.cursor = (0, 0);
+                self.clear_moves();
+                String::new()
+            }
+            Command::Flag(r, c) if self.flag_cycles => {
+                match self.game.cell(r, c).state() {
+                    CellState::Marked(CellMarker::Flagged) => {
+                        self.game.unmark_cell(r, c);
+                        self.game.question_cell(r, c);
+                    }
+                    CellState::Marked(CellMarker::Questioned) => self.game.unmark_cell(r, c),
+                    _ => self.game.flag_cell(r, c),
+                }
+                String::new()
+            }
+            Command::Flag(r, c) => {
+                self.game.toggle_mark(r, c, CellMarker::Flagged);
+                String::new()
+            }
+            Command::Question(r, c)
+                if matches!(self.game.cell(r, c).state(), CellState::Marked(_)) =>
+            {
+                self.game.toggle_mark(r, c, CellMarker::Questioned);
+                String::new()
+            }
+            // a forbidden question mark is reported, rather than silently ignored
+            Command::Question(r, c) => match self.game.try_question_cell(r, c) {
+                Ok(()) => String::new(),
+                Err(e) => e.to_string(),
+            },
+            Command::Reveal(r, c) => {
+                let outcome = if self.safe_first_reveal {
+                    self.game.first_reveal(r, c)
+                } else {
+                    self.game.reveal_cell(r, c)
+                };
+                match outcome {
+                    RevealOutcome::Opening { newly_revealed }
+                    | RevealOutcome::Number { newly_revealed, .. }
+                        if newly_revealed > 1 =>
+                    {
+                        format!("opened {} cells", newly_revealed)
+                    }
+                    RevealOutcome::AlreadyRevealed => "that square is already revealed".to_string(),
+                    RevealOutcome::Protected => {
+                        "that square holds a protected question mark".to_string()
+                    }
+                    _ => String::new(),
+                }
+            }
+            // a free reveal is always safe, and is made without moving any mines
+            Command::FreeReveal(r, c) => {
+                self.game.reveal_cell(r, c);
+                String::new()
+            }
+            // a safe reveal goes through the grid's first reveal, whatever the driver's setting
+            Command::SafeReveal(r, c) => {
+                self.game.first_reveal(r, c);
+                String::new()
+            }
+            // in training mode the chord may remove misplaced flags instead, each flag it
+            // actually removed is reported
+            Command::Chord(r, c) => {
+                let misplaced = if self.game.training_mode() {
+                    self.game.misplaced_flags(r, c)
+                } else {
+                    vec![]
+                };
+                self.game.chord_cell(r, c);
+                misplaced
+                    .iter()
+                    .filter(|(mr, mc)| *self.game.cell(*mr, *mc).state() == CellState::Hidden)
+                    .map(|(mr, mc)| format!("the flag at {} {} is misplaced, removing it", mr, mc))
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            }
+            Command::Info(r, c) => self.cell_info(r, c),
+            Command::Free => match self.game.reveal_random_safe() {
+                Some((r, c)) => {
+                    self.move_log.push(Command::FreeReveal(r, c));
+                    format!(
+                        "revealed {} {}, free moves used: {}",
+                        r,
+                        c,
+                        self.game.free_moves()
+                    )
+                }
+                None => "there are no safe squares left to reveal".to_string(),
+            },
+            Command::Safe => match self.game.reveal_safest() {
+                Some(((r, c), probability)) => {
+                    self.move_log.push(Command::SafeReveal(r, c));
+                    format!(
+                    "revealed {} {}, it had a {:.1}% chance of holding a mine, safe assists used: {}",
+                    r,
+                    c,
+                            probability * 100.0,
+                            self.game.safe_assists()
+                        )
+                }
+                None if self.game.remaining_safe_cells() == 0 => {
+                    "there are no squares left to reveal".to_string()
+                }
+                None => "there are too many possible mine placements to pick the safest square"
+                    .to_string(),
+            },
+            Command::Replay => {
+                let frames = self.replay_frames();
+                if frames.is_empty() {
+                    return "there are no moves to replay".to_string();
+                }
+                for (step, frame) in frames.iter().enumerate() {
+                    println!("replay step {} of {}", step, frames.len() - 1);
+                    println!("{}", frame);
+                    thread::sleep(self.replay_delay);
+                }
+                "end of replay".to_string()
+            }
+            Command::UndoReveal => {
+                if self.game.undo_reveal() {
+                    String::new()
+                } else {
+                    "nothing to undo".to_string()
+                }
+            }
+            Command::ClearMarks => {
+                self.game.clear_markers();
+                String::new()
+            }
+            Command::Training => {
+                let training_mode = !self.game.training_mode();
+                self.game.set_training_mode(training_mode);
+                format!("training mode: {}", training_mode)
+            }
+            Command::Notation => {
+                self.chess_notation = !self.chess_notation;
+                format!("chess notation: {}", self.chess_notation)
+            }
+            Command::Moves => self
+                .available_commands()
+                .iter()
+                .filter_map(|command| match command {
+                    Command::Reveal(r, c) => Some(format!("r {} {}", r, c)),
+                    Command::Flag(r, c) => Some(format!("f {} {}", r, c)),
+                    Command::Chord(r, c) => Some(format!("c {} {}", r, c)),
+                    _ => None,
+                })
+                .collect::<Vec<String>>()
+                .join(", "),
+            Command::Settings => self.settings(),
+            Command::Custom(name) => match self.custom_commands.get(&name) {
+                Some(command) => command(&mut self.game),
+                None => format!("invalid command {}", name),
+            },
+            Command::Xray => {
+                let xray = !self.game.xray();
+                self.game.set_xray(xray);
+                format!("x-ray: {}", xray)
+            }
+            Command::MoveCursor(dr, dc) => {
+                let (rows, cols) = self.game.dimensions();
+                let r = self.cursor.0.saturating_add_signed(dr).min(rows - 1);
+                let c = self.cursor.1.saturating_add_signed(dc).min(cols - 1);
+                self.cursor = (r, c);
+                String::new()
+            }
+        }
+    }
+
+    /// forgets the moves of the current game, e.g. when a new game is started
+    fn clear_moves(&mut self) {
+        self.start = None;
+        self.move_log.clear();
+    }
+
+    /// plays the move log back on a copy of the starting board and returns the board as drawn
+    /// before the first move and after each move. The live game is left untouched
+    fn replay_frames(&self) -> Vec<String> {
+        let mut frames = vec![];
+        self.play_back(|replay| frames.push(replay.render()));
+        frames
+    }
+
+    /// plays the move log back on a driver for a copy of the starting board, which has the same
+    /// options as this driver. `on_move` is called with the replay before the first move and after
+    /// each move. Returns the replay once every move is played, or `None` if no move was made
+    fn play_back<F>(&self, mut on_move: F) -> Option<CommandLineDriver<Grid<Cell>>>
+    where
+        F: FnMut(&CommandLineDriver<Grid<Cell>>),
+    {
+        let (board, cursor) = self.start.clone()?;
+        let mut replay = CommandLineDriver::new(board);
+        replay.cursor = cursor;
+        replay.chess_notation = self.chess_notation;
+        replay.max_dimension = self.max_dimension;
+        replay.time_limit = self.time_limit;
+        replay.clock = self.clock;
+        replay.replay_delay = self.replay_delay;
+        replay.allow_debug = self.allow_debug;
+        replay.flag_cycles = self.flag_cycles;
+        replay.safe_first_reveal = self.safe_first_reveal;
+
+        on_move(&replay);
+        for command in &self.move_log {
+            match command {
+                // custom commands can't be copied, so the ones of this driver are run on the replay
+                Command::Custom(name) => {
+                    if let Some(custom) = self.custom_commands.get(name) {
+                        custom(&mut replay.game);
+                    }
+                }
+                _ => {
+                    replay.execute(command.clone());
+                }
+            }
+            on_move(&replay);
+        }
+        Some(replay)
+    }
+
+    /// returns the moves that make sense in the current state of the game: revealing or flagging
+    /// each hidden or questioned cell and chording each satisfied number. Protected question
+    /// marks are not offered for a reveal. Flags are left out while they are
+    /// disabled. There are none once the game is over
+    fn available_commands(&self) -> Vec<Command> {
+        if self.game.is_game_lost() || self.game.is_game_won() {
+            return vec![];
+        }
+        let (rows, cols) = self.game.dimensions();
+        let mut commands = vec![];
+        for r in 0..rows {
+            for c in 0..cols {
+                let state = *self.game.cell(r, c).state();
+                if matches!(
+                    state,
+                    CellState::Hidden | CellState::Marked(CellMarker::Questioned)
+                ) {
+                    let protected = self.game.protect_questioned() && state != CellState::Hidden;
+                    if !protected {
+                        commands.push(Command::Reveal(r, c));
+                    }
+                    if self.game.flags_enabled() {
+                        commands.push(Command::Flag(r, c));
+                    }
+                } else if self.game.can_chord(r, c) {
+                    commands.push(Command::Chord(r, c));
+                }
+            }
+        }
+        commands
+    }
+
+    /// draws the grid, the cell under the cursor is marked with a `>`
+    fn render(&self) -> String {
+        let (rows, cols) = self.game.dimensions();
+        let mut buf = String::new();
+        // in chess notation, columns are labelled with letters and rows with numbers. Every
+        // column is as wide as the widest label, so that the labels past Z line up with the cells
+        let label_width = rows.to_string().len();
+        let col_width = if self.chess_notation {
+            column_label(cols - 1).len()
+        } else {
+            1
+        };
+        if self.chess_notation {
+            buf.push_str(" ".repeat(label_width).as_str());
+            for c in 0..cols {
+                buf.push_str(format!(" {:>width$}", column_label(c), width = col_width).as_str());
+            }
+            buf.push('\n');
+        }
+        for r in 0..rows {
+            if self.chess_notation {
+                buf.push_str(format!("{:>width$}", r + 1, width = label_width).as_str());
+            }
+            for c in 0..cols {
+                let prefix = if (r, c) == self.cursor { '>' } else { ' ' };
+                buf.push_str(" ".repeat(col_width - 1).as_str());
+                buf.push(prefix);
+                buf.push(self.game.glyph(r, c));
+            }
+            buf.push('\n')
+        }
+        buf
+    }
+
+    /// returns a printable list of the rules and settings the current game is played with. The
+    /// mines can be worked out from the seed, so it is only shown once the game is over, or if
+    /// debugging is allowed
+    fn settings(&self) -> String {
+        let game_over = self.game.is_game_lost() || self.game.is_game_won();
+        let (rows, cols) = self.game.dimensions();
+        let win_condition = match self.game.win_condition() {
+            WinCondition::FlagAllMines => "flag every mine",
+            WinCondition::RevealAllSafe => "reveal every square without a mine",
+        };
+        [
+            format!(
+                "board: {}x{} with {} mines",
+                rows,
+                cols,
+                self.game.total_mines()
+            ),
+            format!("mine density: {:.1}%", self.game.actual_density() * 100.0),
+            format!("difficulty: {}", self.game.difficulty_label()),
+            match self.game.seed() {
+                Some(seed) if game_over || self.allow_debug => format!("seed: {}", seed),
+                Some(_seed) => "seed: hidden until the game is over".to_string(),
+                None => "seed: none".to_string(),
+            },
+            format!("win condition: {}", win_condition),
+            format!("flags: {}", self.game.flags_enabled()),
+            format!("question marks: {}", self.game.questions_enabled()),
+            format!("flag cycles: {}", self.flag_cycles),
+            format!(
+                "protected question marks: {}",
+                self.game.protect_questioned()
+            ),
+            format!("auto chord: {}", self.game.auto_chord()),
+            format!("training mode: {}", self.game.training_mode()),
+            format!("x-ray: {}", self.game.xray()),
+            format!("chess notation: {}", self.chess_notation),
+            format!("theme: {}", self.game.theme().name),
+        ]
+        .join("\n")
+    }
+
+    /// returns a printable description of the cell at `r`,`c` and its neighbors. The kind and
+    /// adjacent mine count of a cell are only shown once it is revealed, or the game is over
+    fn cell_info(&self, r: usize, c: usize) -> String {
+        let game_over = self.game.is_game_lost() || self.game.is_game_won();
+        let describe = |r: usize, c: usize| {
+            let cell = self.game.cell(r, c);
+            let state = match cell.state() {
+                CellState::Revealed => "revealed",
+                CellState::Marked(CellMarker::Flagged) => "flagged",
+                CellState::Marked(CellMarker::Questioned) => "questioned",
+                CellState::Hidden => "hidden",
+            };
+            if game_over || *cell.state() == CellState::Revealed {
+                let kind = match cell.kind() {
+                    CellKind::Mine => "mine",
+                    CellKind::Empty => "empty",
+                };
+                format!(
+                    "{} {} {}, {}, adjacent mines: {}",
+                    r,
+                    c,
+                    state,
+                    kind,
+                    cell.adj_mine_count()
+                )
+            } else {
+                format!("{} {} {}", r, c, state)
+            }
+        };
+
+        let mut info = format!("cell {}\nneighbors:", describe(r, c));
+        for (nr, nc) in self.game.neighbors(r, c) {
+            info.push_str(format!("\n  {}", describe(nr, nc)).as_str());
+        }
+        info
+    }
+
+    /// reads the next command line from `input`, returns `None` once the input has ended
+    fn read_line<R: BufRead>(input: &mut R) -> io::Result<Option<String>> {
+        println!("make a move:");
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(line.trim().to_string()))
+    }
+
+    /// saves the game to the auto-save path, if one is set
+    fn save_on_exit(&mut self) {
+        self.game.pause_timer();
+        if let Some(path) = &self.auto_save {
+            match serde_json::to_string(&self.game)
+                .map_err(io::Error::from)
+                .and_then(|json| fs::write(path, json))
+            {
+                Ok(()) => println!("game saved to {}", path.display()),
+                Err(e) => println!("could not save the game to {}: {}", path.display(), e),
+            }
+        }
+    }
+
+    fn parse_int(&self, s: &str) -> Result<usize, io::Error> {
+        let idx = s.parse::<usize>().map_err(|_e| {
+            io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid index given {}", s),
+            )
+        })?;
+        Ok(idx)
+    }
+
+    /// parses the number of rows or columns of a new game, which must be in the range
+    /// 1..=max_dimension
+    fn parse_dimension(&self, s: &str) -> Result<usize, io::Error> {
+        let dimension = self.parse_int(s)?;
+        if (1..=self.max_dimension).contains(&dimension) {
+            Ok(dimension)
+        } else {
+            Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "the dimension {} is out of the range 1..={}",
+                    dimension, self.max_dimension
+                ),
+            ))
+        }
+    }
+
+    fn check_index_bounds(idx: usize, max_idx: usize) -> Result<bool, io::Error> {
+        if (0..max_idx).contains(&idx) {
+            Ok(true)
+        } else {
+            Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("the index {} is out of the range 0..{}", idx, max_idx),
+            ))
+        }
+    }
+
+    /// maps a minesweeper "move" into a minesweeper `Command` enum
+    /// # Examples
+    /// * "r 0 1" to reveal the cell at row 0 col 1
+    /// * "f 1 2" to place a flag at row 1 col 2
+    /// * "q 2 3" to place a question at row 2 col 3
+    /// * "c 1 1" to chord the cell at row 1 col 1
+    /// * "info 1 2" to describe the cell at row 1 col 2
+    fn map_move(&self, command: &str, row: &str, col: &str) -> Result<Command, io::Error> {
+        let r = self.parse_int(row)?;
+        let c = self.parse_int(col)?;
+        self.map_index_move(command, r, c)
+    }
+
+    /// maps a minesweeper "move" given in chess notation into a minesweeper `Command` enum
+    /// # Examples
+    /// * "r B3" to reveal the cell at row 2 col 1
+    /// * "f AA10" to place a flag at row 9 col 26
+    fn map_chess_move(&self, command: &str, coord: &str) -> Result<Command, io::Error> {
+        let (r, c) = parse_chess_coord(coord).ok_or_else(|| {
+            io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid coordinate given {}", coord),
+            )
+        })?;
+        self.map_index_move(command, r, c)
+    }
+
+    /// checks that `r`,`c` is on the board and maps the move into a `Command`
+    fn map_index_move(&self, command: &str, r: usize, c: usize) -> Result<Command, io::Error> {
+        CommandLineDriver::check_index_bounds(r, self.game.dimensions().0)?;
+        CommandLineDriver::check_index_bounds(c, self.game.dimensions().1)?;
+        match command {
+            "r" | "reveal" => Ok(Command::Reveal(r, c)),
+            "f" | "flag" => Ok(Command::Flag(r, c)),
+            "q" => Ok(Command::Question(r, c)),
+            "c" => Ok(Command::Chord(r, c)),
+            "info" => Ok(Command::Info(r, c)),
+            _ => Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid command {}", command),
+            )),
+        }
+    }
+
+    /// parses the entered command string
+    fn parse_command_line(&self, command_str: &str) -> Result<Command, io::Error> {
+        let toks = command_str.split_whitespace().collect::<Vec<&str>>();
+        if toks.is_empty() {
+            return Ok(Command::Show);
+        }
+        if toks[0] == "load-layout" {
+            return parse_inline_layout(command_str.trim_start()["load-layout".len()..].trim())
+                .map(Command::LoadLayout);
+        }
+        match toks[0] {
+            "quit" => Ok(Command::Quit),
+            "debug" => Ok(Command::Debug),
+            "training" => Ok(Command::Training),
+            "undo-reveal" => Ok(Command::UndoReveal),
+            "clearmarks" => Ok(Command::ClearMarks),
+            "free" => Ok(Command::Free),
+            "safe" => Ok(Command::Safe),
+            "notation" => Ok(Command::Notation),
+            "xray" => Ok(Command::Xray),
+            "moves" => Ok(Command::Moves),
+            "settings" => Ok(Command::Settings),
+            "show" => Ok(Command::Show),
+            "replay" => Ok(Command::Replay),
+            "up" => Ok(Command::MoveCursor(-1, 0)),
+            "down" => Ok(Command::MoveCursor(1, 0)),
+            "left" => Ok(Command::MoveCursor(0, -1)),
+            "right" => Ok(Command::MoveCursor(0, 1)),
+            "reveal" if toks.len() == 1 => Ok(Command::Reveal(self.cursor.0, self.cursor.1)),
+            "flag" if toks.len() == 1 => Ok(Command::Flag(self.cursor.0, self.cursor.1)),
+            "n" if toks.len() == 3 || toks.len() == 4 => {
+                let r = self.parse_dimension(toks[1])?;
+                let c = self.parse_dimension(toks[2])?;
+                let seed = match toks.get(3) {
+                    Some(seed) => Some(seed.parse::<u64>().map_err(|_e| {
+                        io::Error::new(
+                            ErrorKind::InvalidInput,
+                            format!("invalid seed given {}", seed),
+                        )
+                    })?),
+                    None => None,
+                };
+                Ok(Command::New(r, c, seed))
+            }
+            "load-code" if toks.len() == 2 => Ok(Command::LoadCode(toks[1].to_string())),
+            "r" | "f" | "q" | "c" | "info" if toks.len() == 3 => {
+                self.map_move(toks[0], toks[1], toks[2])
+            }
+            "r" | "f" | "q" | "c" | "info" | "reveal" | "flag"
+                if toks.len() == 2 && self.chess_notation =>
+            {
+                self.map_chess_move(toks[0], toks[1])
+            }
+            name if toks.len() == 1 && self.custom_commands.contains_key(name) => {
+                Ok(Command::Custom(name.to_string()))
+            }
+            _ => Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid command {}", command_str),
+            )),
+        }
+    }
+}
+
+/// splits a line holding several commands separated by `;`, e.g. `r 0 0; f 1 1; q 2 2`, into
+/// the individual commands, in the order they should be applied
+fn split_batch(batch_str: &str) -> Vec<&str> {
+    batch_str
+        .split(';')
+        .map(|command_str| command_str.trim())
+        .filter(|command_str| !command_str.is_empty())
+        .collect()
+}
+
+/// parses a quoted, single line layout such as `"*../..*"`, where `/` separates the rows, into
+/// the multi line layout expected by `Grid::from_layout`
+fn parse_inline_layout(quoted: &str) -> Result<String, io::Error> {
+    quoted
+        .strip_prefix('"')
+        .and_then(|layout| layout.strip_suffix('"'))
+        .map(|layout| layout.replace('/', "\n"))
+        .ok_or_else(|| {
+            io::Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "the layout must be quoted, e.g. \"*../..*\", got {}",
+                    quoted
+                ),
+            )
+        })
+}
+
+/// formats `value` like the three digit LED mine counter of the classic game, zero padded with a
+/// leading `-` for negative values. Values that do not fit are clamped to -99..=999
+fn counter_display(value: isize) -> String {
+    let value = value.clamp(-99, 999);
+    if value < 0 {
+        format!("-{:02}", -value)
+    } else {
+        format!("{:03}", value)
+    }
+}
+
+/// returns the chess notation label of the column at index `c`: A..Z, then AA, AB, ...
+fn column_label(c: usize) -> String {
+    let mut label = vec![];
+    let mut n = c + 1;
+    while n > 0 {
+        label.push((b'A' + ((n - 1) % 26) as u8) as char);
+        n = (n - 1) / 26;
+    }
+    label.iter().rev().collect()
+}
+
+/// parses a chess notation coordinate, such as `B3` or `AA10`, into a zero based row,col index
+fn parse_chess_coord(coord: &str) -> Option<(usize, usize)> {
+    let split = coord.find(|ch: char| ch.is_ascii_digit())?;
+    let (letters, digits) = coord.split_at(split);
+    if letters.is_empty() || !letters.chars().all(|ch| ch.is_ascii_alphabetic()) {
+        return None;
+    }
+    let col = letters
+        .to_ascii_uppercase()
+        .bytes()
+        .try_fold(0usize, |col, b| {
+            col.checked_mul(26)?.checked_add((b - b'A') as usize + 1)
+        })?;
+    let row = digits.parse::<usize>().ok()?;
+    Some((row.checked_sub(1)?, col - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        column_label, counter_display, parse_chess_coord, split_batch, Command, CommandLineDriver,
+    };
+    use crate::mine_sweeper_board::{
+        CellMarker, CellState, MineSweeperCell, MineSweeperGame, XRAY,
+    };
+    use crate::mine_sweeper_impl::{Cell, Grid};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn info_lists_the_neighbors_of_a_hidden_corner_cell() {
+        let driver = CommandLineDriver::new(Grid::init(3, 3));
+        assert_eq!(
+            driver.cell_info(0, 0),
+            "cell 0 0 hidden\nneighbors:\n  0 1 hidden\n  1 0 hidden\n  1 1 hidden"
+        );
+    }
+
+    /// parses and executes each command in `commands`
+    fn run(driver: &mut CommandLineDriver<Grid<Cell>>, commands: &[&str]) {
+        for command in commands {
+            let command = driver.parse_command_line(command).unwrap();
+            driver.execute(command);
+        }
+    }
+
+    #[test]
+    fn reveal_acts_on_the_cursor_position() {
+        let mut driver = CommandLineDriver::new(Grid::init(4, 4));
+        run(
+            &mut driver,
+            &["down", "down", "right", "up", "left", "left", "right"],
+        );
+        assert_eq!(driver.cursor, (1, 1));
+
+        run(&mut driver, &["reveal"]);
+        assert_eq!(*driver.game.cell(1, 1).state(), CellState::Revealed);
+    }
+
+    #[test]
+    fn cursor_stays_on_the_board() {
+        let mut driver = CommandLineDriver::new(Grid::init(2, 2));
+        run(&mut driver, &["up", "left"]);
+        assert_eq!(driver.cursor, (0, 0));
+        run(&mut driver, &["down", "down", "right", "right"]);
+        assert_eq!(driver.cursor, (1, 1));
+        assert!(driver.render().contains('>'));
+    }
+
+    #[test]
+    fn ending_the_input_auto_saves_the_game_and_exits() {
+        let path = std::env::temp_dir().join("minesweeper_auto_save_test.json");
+        let _ = std::fs::remove_file(&path);
+        let mut driver = CommandLineDriver::new(Grid::init(4, 4));
+        driver.auto_save = Some(path.clone());
+
+        driver.run(&mut "f 0 0\n".as_bytes());
+
+        let saved: Grid<Cell> =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(saved.cell(0, 0).is_flagged());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn chess_notation_coordinates_map_to_row_and_column_indices() {
+        let mut driver = CommandLineDriver::new(Grid::init(5, 30));
+        driver.chess_notation = true;
+        assert!(matches!(
+            driver.parse_command_line("r B3"),
+            Ok(Command::Reveal(2, 1))
+        ));
+        assert!(matches!(
+            driver.parse_command_line("flag AB5"),
+            Ok(Command::Flag(4, 27))
+        ));
+        assert!(driver.parse_command_line("r B6").is_err());
+        assert!(driver.parse_command_line("r B0").is_err());
+
+        assert_eq!(parse_chess_coord("a1"), Some((0, 0)));
+        assert_eq!(parse_chess_coord("3B"), None);
+        assert_eq!(column_label(0), "A");
+        assert_eq!(column_label(25), "Z");
+        assert_eq!(column_label(26), "AA");
+        assert_eq!(column_label(27), "AB");
+        // a column too large to count is rejected instead of overflowing
+        assert_eq!(parse_chess_coord("AAAAAAAAAAAAAAA1"), None);
+        assert!(driver.parse_command_line("r AAAAAAAAAAAAAAA1").is_err());
+    }
+
+    #[test]
+    fn chess_notation_labels_line_up_with_the_columns_past_z() {
+        let mut driver = CommandLineDriver::new(Grid::init(2, 28));
+        driver.chess_notation = true;
+        let rendered = driver.render();
+        let lines = rendered
+            .lines()
+            .map(|line| line.chars().collect::<Vec<char>>())
+            .collect::<Vec<Vec<char>>>();
+        assert_eq!(lines[0].len(), lines[1].len());
+        // each label ends right above the glyph of its column, three characters apart
+        for c in 0..28 {
+            let label = column_label(c);
+            assert_eq!(lines[0][3 + 3 * c], label.chars().last().unwrap());
+            assert_eq!(lines[1][3 + 3 * c], driver.game.glyph(0, c));
+        }
+    }
+
+    #[test]
+    fn batch_lines_parse_into_a_command_sequence() {
+        let driver = CommandLineDriver::new(Grid::init(4, 4));
+        let commands = split_batch("r 0 0; f 1 1;q 2 2;")
+            .into_iter()
+            .map(|command_str| driver.parse_command_line(command_str).unwrap())
+            .collect::<Vec<Command>>();
+        assert!(matches!(
+            commands.as_slice(),
+            [
+                Command::Reveal(0, 0),
+                Command::Flag(1, 1),
+                Command::Question(2, 2)
+            ]
+        ));
+    }
+
+    #[test]
+    fn batch_lines_stop_at_the_first_command_that_ends_the_game() {
+        let mut driver = CommandLineDriver::new(Grid::init(4, 4));
+        // the mine is hit on the first move, which is only possible without a safe first reveal
+        driver.safe_first_reveal = false;
+        let (r, c) = driver.game.first_mine().unwrap();
+        let batch = format!("r {} {}; f 3 3\n", r, c);
+        driver.run(&mut batch.as_bytes());
+
+        assert!(driver.game.is_game_lost());
+        assert!(!driver.game.cell(3, 3).is_flagged());
+    }
+
+    #[test]
+    fn reveals_report_how_many_cells_they_opened() {
+        let mut driver = CommandLineDriver::new(Grid::from_layout("*...\n....\n....").unwrap());
+        driver.safe_first_reveal = false;
+        assert_eq!(driver.execute(Command::Reveal(2, 3)), "opened 11 cells");
+        assert_eq!(
+            driver.execute(Command::Reveal(2, 3)),
+            "that square is already revealed"
+        );
+    }
+
+    #[test]
+    fn forbidden_question_marks_are_reported() {
+        let mut driver = CommandLineDriver::new(Grid::from_layout("*..\n...").unwrap());
+        run(&mut driver, &["q 1 1", "q 1 1"]);
+        assert_eq!(*driver.game.cell(1, 1).state(), CellState::Hidden);
+
+        driver.game.set_questions_enabled(false);
+        assert_eq!(
+            driver.execute(Command::Question(1, 2)),
+            "can't place a question mark at 1,2, question marks are forbidden"
+        );
+        assert_eq!(*driver.game.cell(1, 2).state(), CellState::Hidden);
+        assert_eq!(driver.game.questions_used(), 1);
+    }
+
+    #[test]
+    fn clearmarks_removes_the_markers_but_not_the_reveals() {
+        let mut driver = CommandLineDriver::new(Grid::from_layout("*.*\n...\n*.*").unwrap());
+        driver.safe_first_reveal = false;
+        run(&mut driver, &["r 1 1", "f 0 0", "q 2 1", "clearmarks"]);
+        assert!(!driver.game.cell(0, 0).is_flagged());
+        assert_eq!(*driver.game.cell(2, 1).state(), CellState::Hidden);
+        assert_eq!(*driver.game.cell(1, 1).state(), CellState::Revealed);
+    }
+
+    #[test]
+    fn xray_marks_the_mines_while_the_game_goes_on() {
+        let mut driver = CommandLineDriver::new(Grid::init(4, 4));
+        assert!(!driver.render().contains(XRAY));
+
+        assert_eq!(
+            driver.execute(driver.parse_command_line("xray").unwrap()),
+            "x-ray: true"
+        );
+        let mines = driver.game.mine_indices().len();
+        assert_eq!(driver.render().matches(XRAY).count(), mines);
+        assert!(!driver.game.is_game_lost() && !driver.game.is_game_won());
+    }
+
+    #[test]
+    fn available_commands_offers_reveals_flags_and_chords() {
+        let mut driver = CommandLineDriver::new(Grid::from_layout("*..\n..*").unwrap());
+        driver.safe_first_reveal = false;
+        // a questioned cell can still be revealed and flagged
+        run(&mut driver, &["f 0 0", "r 1 0", "r 1 1", "q 0 2"]);
+        assert_eq!(
+            driver.available_commands(),
+            vec![
+                Command::Reveal(0, 1),
+                Command::Flag(0, 1),
+                Command::Reveal(0, 2),
+                Command::Flag(0, 2),
+                Command::Chord(1, 0),
+                Command::Reveal(1, 2),
+                Command::Flag(1, 2)
+            ]
+        );
+        assert_eq!(
+            driver.execute(Command::Moves),
+            "r 0 1, f 0 1, r 0 2, f 0 2, c 1 0, r 1 2, f 1 2"
+        );
+        // unless the question mark is protected from reveals
+        driver.game.set_protect_questioned(true);
+        assert!(!driver.available_commands().contains(&Command::Reveal(0, 2)));
+        assert!(driver.available_commands().contains(&Command::Flag(0, 2)));
+        driver.game.set_protect_questioned(false);
+
+        // once every cell is revealed there is nothing left to reveal
+        run(&mut driver, &["r 0 1", "r 0 2", "f 1 2"]);
+        assert!(!driver
+            .available_commands()
+            .iter()
+            .any(|command| matches!(command, Command::Reveal(_, _))));
+    }
+
+    #[test]
+    fn training_chords_report_only_the_flags_they_remove() {
+        let mut driver = CommandLineDriver::new(Grid::from_layout("*.*\n...\n*.*").unwrap());
+        driver.safe_first_reveal = false;
+        run(&mut driver, &["training", "r 1 1", "f 0 1"]);
+        // the 4 in the middle is not satisfied by one flag, so the chord removes nothing
+        assert_eq!(driver.execute(Command::Chord(1, 1)), "");
+        assert!(driver.game.cell(0, 1).is_flagged());
+
+        run(&mut driver, &["f 0 0", "f 0 2", "f 1 0"]);
+        assert_eq!(
+            driver.execute(Command::Chord(1, 1)),
+            "the flag at 0 1 is misplaced, removing it\nthe flag at 1 0 is misplaced, removing it"
+        );
+        assert_eq!(*driver.game.cell(0, 1).state(), CellState::Hidden);
+        assert!(driver.game.cell(0, 0).is_flagged());
+    }
+
+    #[test]
+    fn available_commands_leaves_out_flags_while_they_are_disabled() {
+        let mut driver = CommandLineDriver::new(Grid::from_layout("*..\n..*").unwrap());
+        driver.game.set_flags_enabled(false);
+        driver.safe_first_reveal = false;
+        run(&mut driver, &["r 1 0"]);
+        assert_eq!(
+            driver.available_commands(),
+            vec![
+                Command::Reveal(0, 0),
+                Command::Reveal(0, 1),
+                Command::Reveal(0, 2),
+                Command::Reveal(1, 1),
+                Command::Reveal(1, 2)
+            ]
+        );
+        assert_eq!(
+            driver.execute(Command::Moves),
+            "r 0 0, r 0 1, r 0 2, r 1 1, r 1 2"
+        );
+    }
+
+    #[test]
+    fn safe_reveals_the_square_that_cannot_hold_a_mine() {
+        // the revealed bottom row proves the mines are in the top corners
+        let grid = Grid::from_state_layout("*.*\n121").unwrap();
+        let mut driver = CommandLineDriver::new(grid);
+        assert_eq!(
+            driver.execute(Command::Safe),
+            "revealed 0 1, it had a 0.0% chance of holding a mine, safe assists used: 1"
+        );
+        assert_eq!(*driver.game.cell(0, 1).state(), CellState::Revealed);
+        assert_eq!(driver.game.summary().safe_assists, 1);
+    }
+
+    #[test]
+    fn settings_lists_the_active_options() {
+        let mut driver = CommandLineDriver::new(Grid::init(10, 10));
+        driver.game.set_questions_enabled(false);
+        run(&mut driver, &["training", "notation"]);
+
+        let settings = driver.execute(Command::Settings);
+        assert!(settings.contains("board: 10x10 with 15 mines"));
+        assert!(settings.contains("mine density: 15.0%"));
+        assert!(settings.contains("win condition: flag every mine"));
+        assert!(settings.contains("question marks: false"));
+        assert!(settings.contains("training mode: true"));
+        assert!(settings.contains("chess notation: true"));
+        assert!(settings.contains("theme: classic"));
+    }
+
+    #[test]
+    fn counter_display_pads_like_an_led_counter() {
+        assert_eq!(counter_display(7), "007");
+        assert_eq!(counter_display(0), "000");
+        assert_eq!(counter_display(99), "099");
+        assert_eq!(counter_display(-3), "-03");
+        assert_eq!(counter_display(-120), "-99");
+        assert_eq!(counter_display(1000), "999");
+
+        let mut driver = CommandLineDriver::new(Grid::from_layout("*..\n..*").unwrap());
+        run(&mut driver, &["f 0 0", "f 0 1", "f 0 2"]);
+        assert_eq!(driver.game.mines_remaining(), -1);
+    }
+
+    #[test]
+    fn show_redraws_without_changing_the_game() {
+        let mut driver = CommandLineDriver::new(Grid::from_layout("*..\n...\n..*").unwrap());
+        run(&mut driver, &["r 0 1", "f 0 0"]);
+        let before = driver.render();
+        let states = |driver: &CommandLineDriver<Grid<Cell>>| {
+            (0..9)
+                .map(|ndx| *driver.game.cell(ndx / 3, ndx % 3).state())
+                .collect::<Vec<CellState>>()
+        };
+        let expected = states(&driver);
+
+        assert_eq!(driver.parse_command_line("").unwrap(), Command::Show);
+        assert_eq!(driver.execute(Command::Show), "");
+        run(&mut driver, &["show"]);
+        assert_eq!(states(&driver), expected);
+        assert_eq!(driver.render(), before);
+    }
+
+    #[test]
+    fn new_games_larger_than_the_maximum_dimension_are_rejected() {
+        let mut driver = CommandLineDriver::new(Grid::init(3, 3));
+        let err = driver.parse_command_line("n 100000 100000").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "the dimension 100000 is out of the range 1..=1000"
+        );
+        assert!(driver.parse_command_line("n 0 5").is_err());
+
+        driver.max_dimension = 10;
+        assert!(driver.parse_command_line("n 11 5").is_err());
+        assert_eq!(
+            driver.parse_command_line("n 10 5").unwrap(),
+            Command::New(10, 5, None)
+        );
+    }
+
+    #[test]
+    fn load_layout_replaces_the_game_with_an_inline_layout() {
+        let mut driver = CommandLineDriver::new(Grid::init(5, 5));
+        let command = driver
+            .parse_command_line("load-layout \"*../..*\"")
+            .unwrap();
+        assert_eq!(command, Command::LoadLayout("*..\n..*".to_string()));
+        assert_eq!(driver.execute(command), "");
+        assert_eq!(driver.game.dimensions(), (2, 3));
+        assert_eq!(driver.game.mine_indices(), vec![(0, 0), (1, 2)]);
+
+        assert!(driver.parse_command_line("load-layout *..").is_err());
+        let command = driver.parse_command_line("load-layout \"*../.\"").unwrap();
+        assert_eq!(
+            driver.execute(command),
+            "could not load the layout: line 1 of the layout has 1 cells, expected 3"
+        );
+        assert_eq!(driver.game.dimensions(), (2, 3));
+    }
+
+    /// a clock running an hour ahead of the real one
+    fn an_hour_from_now() -> Instant {
+        Instant::now() + Duration::from_secs(3600)
+    }
+
+    #[test]
+    fn running_out_of_time_ends_the_game() {
+        let mut driver = CommandLineDriver::new(Grid::from_layout("*..\n...\n..*").unwrap());
+        driver.time_limit = Some(Duration::from_secs(60));
+        driver.clock = an_hour_from_now;
+        // the timer has not started yet, so no time has been played
+        assert!(!driver.is_time_up());
+
+        driver.run(&mut "r 0 1\nf 0 0\n".as_bytes());
+        assert!(!driver.game.is_game_won());
+        assert!(driver.game.is_game_lost());
+        assert!(driver.game.lost_on_time());
+        assert!(driver
+            .outcome()
+            .unwrap()
+            .starts_with("time up\nthe time limit ran out\n"));
+        assert!(driver.game.summary().to_string().ends_with(", out of time"));
+        // the game ended before the flag was placed
+        assert!(!driver.game.cell(0, 0).is_flagged());
+
+        driver.time_limit = None;
+        assert!(!driver.is_time_up());
+    }
+
+    #[test]
+    fn replay_reproduces_the_game_without_changing_it() {
+        let mut driver = CommandLineDriver::new(Grid::from_layout("*.*.\n....\n*..*").unwrap());
+        driver.replay_delay = Duration::ZERO;
+        assert_eq!(
+            driver.execute(Command::Replay),
+            "there are no moves to replay"
+        );
+
+        run(
+            &mut driver,
+            &["f 0 0", "down", "right", "r 0 1", "q 2 3", "debug", "free"],
+        );
+        assert_eq!(driver.move_log.len(), 6);
+        let frames = driver.replay_frames();
+        assert_eq!(frames.len(), 7);
+        assert_eq!(frames.last(), Some(&driver.render()));
+
+        let render = driver.render();
+        assert_eq!(driver.execute(Command::Replay), "end of replay");
+        assert_eq!(driver.render(), render);
+        assert_eq!(driver.move_log.len(), 6);
+
+        run(&mut driver, &["n 3 3"]);
+        assert!(driver.start.is_none());
+        assert!(driver.move_log.is_empty());
+    }
+
+    #[test]
+    fn replay_uses_the_options_of_the_driver() {
+        // every empty square borders a mine, and a safe first reveal would move mines elsewhere
+        let layout = "*.*.*.*.";
+        let mut driver = CommandLineDriver::new(Grid::from_layout(layout).unwrap());
+        driver.safe_first_reveal = false;
+        driver.flag_cycles = true;
+        run(&mut driver, &["r 0 1", "f 0 3", "f 0 3"]);
+        assert_eq!(
+            *driver.game.cell(0, 3).state(),
+            CellState::Marked(CellMarker::Questioned)
+        );
+
+        let replay = driver.play_back(|_replay| {}).unwrap();
+        assert_eq!(replay.game.mine_indices(), driver.game.mine_indices());
+        assert_eq!(replay.render(), driver.render());
+    }
+
+    #[test]
+    fn replay_plays_back_assisted_reveals_as_they_were_made() {
+        let layout = "*.*.*.*.";
+        let mut driver = CommandLineDriver::new(Grid::from_layout(layout).unwrap());
+        run(&mut driver, &["free"]);
+        assert!(matches!(driver.move_log[..], [Command::FreeReveal(0, _)]));
+        let replay = driver.play_back(|_replay| {}).unwrap();
+        assert_eq!(replay.game.mine_indices(), driver.game.mine_indices());
+        assert_eq!(replay.render(), driver.render());
+
+        // the safe assist moves the mines away from its first reveal, the replay does the same
+        let mut driver = CommandLineDriver::new(Grid::from_layout(layout).unwrap());
+        run(&mut driver, &["safe"]);
+        assert!(matches!(driver.move_log[..], [Command::SafeReveal(0, 0)]));
+        assert_ne!(
+            driver.game.mine_indices(),
+            Grid::from_layout(layout).unwrap().mine_indices()
+        );
+        let replay = driver.play_back(|_replay| {}).unwrap();
+        assert_eq!(replay.game.mine_indices(), driver.game.mine_indices());
+        assert_eq!(replay.render(), driver.render());
+    }
+
+    #[test]
+    fn replay_runs_the_custom_commands_of_the_driver() {
+        let mut driver = CommandLineDriver::new(Grid::from_layout("*..\n...").unwrap());
+        driver.register_command("flag-all", |game| {
+            for (r, c) in game.mine_indices() {
+                game.flag_cell(r, c);
+            }
+            "flagged every mine".to_string()
+        });
+        run(&mut driver, &["flag-all"]);
+        assert!(driver.game.cell(0, 0).is_flagged());
+
+        let frames = driver.replay_frames();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames.last(), Some(&driver.render()));
+    }
+
+    #[test]
+    fn the_win_banner_shows_the_seed_and_dimensions() {
+        let mut driver = CommandLineDriver::new(Grid::init_with_seed(4, 6, 12345));
+        assert_eq!(driver.outcome(), None);
+        for (r, c) in driver.game.mine_indices() {
+            driver.game.flag_cell(r, c);
+        }
+        let outcome = driver.outcome().unwrap();
+        assert!(outcome.starts_with("you win!!\nbeat my time on seed 12345, 4x6: n 4 6 12345\n"));
+
+        // a symmetric board can't be rebuilt from its seed by `n 4 6`, so its code is shown
+        let mut driver = CommandLineDriver::new(Grid::init_symmetric(4, 6, 2, 12345));
+        for (r, c) in driver.game.mine_indices() {
+            driver.game.flag_cell(r, c);
+        }
+        let code = driver.game.to_code();
+        let banner = format!(
+            "you win!!\nbeat my time on board {}: load-code {}\n",
+            code, code
+        );
+        assert!(driver.outcome().unwrap().starts_with(&banner));
+
+        let mut driver = CommandLineDriver::new(Grid::from_layout("*.").unwrap());
+        run(&mut driver, &["f 0 0"]);
+        assert!(driver
+            .outcome()
+            .unwrap()
+            .starts_with("you win!!\nbeat my time on board 1x2."));
+    }
+
+    #[test]
+    fn the_win_banner_starts_the_same_board_for_another_player() {
+        let boards = [
+            Grid::init_with_seed(5, 7, 99),
+            Grid::init_symmetric(4, 6, 2, 12345),
+        ];
+        for board in boards {
+            let mut driver = CommandLineDriver::new(board);
+            for (r, c) in driver.game.mine_indices() {
+                driver.game.flag_cell(r, c);
+            }
+            let outcome = driver.outcome().unwrap();
+            let banner = outcome.lines().nth(1).unwrap();
+            let (_challenge, command_str) = banner.split_once(": ").unwrap();
+
+            let mut friend = CommandLineDriver::new(Grid::init(3, 3));
+            let command = friend.parse_command_line(command_str).unwrap();
+            assert_eq!(friend.execute(command), "");
+            assert_eq!(friend.game.dimensions(), driver.game.dimensions());
+            assert_eq!(friend.game.mine_indices(), driver.game.mine_indices());
+        }
+
+        let mut driver = CommandLineDriver::new(Grid::init(3, 3));
+        assert!(driver.parse_command_line("n 3 3 seven").is_err());
+        let command = driver.parse_command_line("load-code 3x3.!!").unwrap();
+        assert_eq!(
+            driver.execute(command),
+            "could not load the board code: unknown code character '!'"
+        );
+    }
+
+    #[test]
+    fn settings_hide_the_seed_until_the_game_is_over() {
+        let mut driver = CommandLineDriver::new(Grid::init_with_seed(4, 4, 77));
+        driver.allow_debug = false;
+        assert!(driver
+            .settings()
+            .contains("seed: hidden until the game is over"));
+
+        driver.allow_debug = true;
+        assert!(driver.settings().contains("seed: 77"));
+
+        driver.allow_debug = false;
+        let (r, c) = driver.game.first_mine().unwrap();
+        driver.game.reveal_cell(r, c);
+        assert!(driver.settings().contains("seed: 77"));
+    }
+
+    #[test]
+    fn custom_commands_run_unless_a_built_in_has_the_same_name() {
+        let mut driver = CommandLineDriver::new(Grid::from_layout("*..\n...").unwrap());
+        driver.register_command("flag-all", |game| {
+            for (r, c) in game.mine_indices() {
+                game.flag_cell(r, c);
+            }
+            "flagged every mine".to_string()
+        });
+        driver.register_command("xray", |_game| "custom x-ray".to_string());
+
+        let command = driver.parse_command_line("flag-all").unwrap();
+        assert_eq!(command, Command::Custom("flag-all".to_string()));
+        assert_eq!(driver.execute(command), "flagged every mine");
+        assert!(driver.game.cell(0, 0).is_flagged());
+
+        assert_eq!(driver.parse_command_line("xray").unwrap(), Command::Xray);
+        assert!(driver.parse_command_line("flag-all 1").is_err());
+        assert!(driver.parse_command_line("solve").is_err());
+    }
+
+    #[test]
+    fn debug_can_be_disabled_to_avoid_spoilers() {
+        let mut driver = CommandLineDriver::new(Grid::from_layout("*..\n...").unwrap());
+        let debug = driver.parse_command_line("debug").unwrap();
+        assert_eq!(driver.execute(debug.clone()), format!("{:?}", driver.game));
+
+        driver.allow_debug = false;
+        assert_eq!(driver.execute(debug), "disabled");
+    }
+
+    #[test]
+    fn flag_cycles_through_flag_question_and_clear() {
+        let mut driver = CommandLineDriver::new(Grid::from_layout("*..\n...").unwrap());
+        driver.flag_cycles = true;
+        run(&mut driver, &["f 0 0"]);
+        assert_eq!(
+            *driver.game.cell(0, 0).state(),
+            CellState::Marked(CellMarker::Flagged)
+        );
+        run(&mut driver, &["f 0 0"]);
+        assert_eq!(
+            *driver.game.cell(0, 0).state(),
+            CellState::Marked(CellMarker::Questioned)
+        );
+        run(&mut driver, &["f 0 0"]);
+        assert_eq!(*driver.game.cell(0, 0).state(), CellState::Hidden);
+
+        // by default, flagging toggles the flag
+        driver.flag_cycles = false;
+        run(&mut driver, &["f 0 0", "f 0 0"]);
+        assert_eq!(*driver.game.cell(0, 0).state(), CellState::Hidden);
+    }
+}
@@ -0,0 +1,43 @@
+//! This module contains the error type returned by the fallible methods of the library
+
+use std::fmt;
+use std::fmt::Formatter;
+
+/// the errors that can occur when operating on a minesweeper grid
+#[derive(Debug, Clone, PartialEq)]
+pub enum MinesweeperError {
+    /// the row,col index `r`,`c` falls outside of a grid with `rows` rows and `cols` columns
+    OutOfBounds {
+        r: usize,
+        c: usize,
+        rows: usize,
+        cols: usize,
+    },
+    /// the cell at `r`,`c` holds a mine, so revealing it would lose the game
+    HitMine { r: usize, c: usize },
+    /// a question mark can't be placed at `r`,`c`, because question marks (or all markers) are
+    /// forbidden in this game
+    QuestionsForbidden { r: usize, c: usize },
+}
+
+impl fmt::Display for MinesweeperError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            MinesweeperError::OutOfBounds { r, c, rows, cols } => write!(
+                f,
+                "the cell {},{} is outside of the {}x{} grid",
+                r, c, rows, cols
+            ),
+            MinesweeperError::HitMine { r, c } => {
+                write!(f, "the cell {},{} holds a mine", r, c)
+            }
+            MinesweeperError::QuestionsForbidden { r, c } => write!(
+                f,
+                "can't place a question mark at {},{}, question marks are forbidden",
+                r, c
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MinesweeperError {}
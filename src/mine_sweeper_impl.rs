@@ -1,16 +1,70 @@
 use crate::mine_sweeper_board::{
-    CellKind, CellMarker, CellState, MineSweeperCell, MineSweeperGame, FLAG, HIDDEN, MINE,
-    QUESTION, REVEALED,
+    CellKind, CellMarker, CellState, GameState, MineDistribution, MineSweeperCell, MineSweeperGame,
+    RevealOutcome, Theme, Transform, WinCondition, MINE, XRAY,
 };
+use crate::mine_sweeper_error::MinesweeperError;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
-use std::collections::HashSet;
+use rand::{thread_rng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt;
 use std::fmt::Formatter;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// the offset basis and prime of the 64 bit FNV-1a hash used by `layout_hash`
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// the default maximum number of mine configurations enumerated by the solver
+pub const DEFAULT_SOLUTION_LIMIT: usize = 100_000;
+
+/// the row and column offsets of the eight cells surrounding a cell, in row major order
+const NEIGHBOR_OFFSETS: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// the maximum number of grids generated by `init_with_3bv_range` while looking for one whose
+/// 3BV is in range
+const MAX_3BV_ATTEMPTS: usize = 1_000;
+
+/// the characters of a board code, each one holds the mine bits of six cells
+const CODE_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+// the RGBA colors used by `to_rgba`
+const HIDDEN_RGBA: [u8; 4] = [160, 160, 160, 255];
+const REVEALED_RGBA: [u8; 4] = [224, 224, 224, 255];
+const MINE_RGBA: [u8; 4] = [0, 0, 0, 255];
+const FLAG_RGBA: [u8; 4] = [220, 0, 0, 255];
+const QUESTION_RGBA: [u8; 4] = [230, 190, 0, 255];
+/// the classic colors of the adjacent mine counts 1 through 8
+const NUMBER_RGBA: [[u8; 4]; 8] = [
+    [0, 0, 255, 255],
+    [0, 128, 0, 255],
+    [255, 0, 0, 255],
+    [0, 0, 128, 255],
+    [128, 0, 0, 255],
+    [0, 128, 128, 255],
+    [0, 0, 0, 255],
+    [128, 128, 128, 255],
+];
+
+/// the probability that a clustered mine grows next to an existing mine, rather than starting
+/// a new cluster
+const CLUSTER_GROWTH: f64 = 0.85;
 
 /// MineSweeper cell
 /// holds the state of a cell in a minesweeper grid
-#[derive(PartialEq)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct Cell {
     state: CellState,
     kind: CellKind,
@@ -19,10 +73,223 @@ pub struct Cell {
 
 /// MineSweeper Grid.
 /// This struct contains a 2D grid of minesweeper cells stored in a 1D vector
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Grid<T: MineSweeperCell> {
     grid: Vec<T>,
     num_rows: usize,
     num_cols: usize,
+    // the number of mines placed on the grid when it was built
+    num_mines: usize,
+    // the seed the mines were placed from, if the grid was generated from one
+    seed: Option<u64>,
+    // the index of the cell whose chord revealed a mine, along with the misplaced flags around it
+    // at the time, if the game was lost by a chord. A cascade can reveal those flags later on
+    detonating_chord: Option<(usize, Vec<(usize, usize)>)>,
+    training_mode: bool,
+    solution_limit: usize,
+    // the cells opened by each reveal, along with the state they had before being revealed
+    reveal_log: Vec<Vec<(usize, CellState)>>,
+    // every cell revealed by play, in the order they were revealed
+    reveal_history: Vec<(usize, usize)>,
+    // the cells still to be opened by a reveal that is spread over several steps
+    pending_reveals: Vec<usize>,
+    started: bool,
+    clicks: usize,
+    // the chords that opened at least one cell, and the number of cells they opened
+    chord_clicks: usize,
+    chord_reveals: usize,
+    // free form notes attached to cells, they have no effect on game play
+    #[serde(with = "note_pairs")]
+    notes: HashMap<(usize, usize), String>,
+    free_moves: usize,
+    safe_assists: usize,
+    theme: Theme,
+    // time played while the timer was not running, i.e. before the game was saved
+    elapsed_time: Duration,
+    // when the timer was (re)started, this is not saved along with the grid
+    #[serde(skip)]
+    timer_start: Option<Instant>,
+    questions_enabled: bool,
+    questions_used: usize,
+    flags_enabled: bool,
+    auto_chord: bool,
+    win_condition: WinCondition,
+    // when set, hidden mines are drawn with a marker. Using it is recorded in the game summary
+    xray: bool,
+    xray_used: bool,
+    protect_questioned: bool,
+    // set when the game was lost by running out of time rather than by revealing a mine
+    lost_on_time: bool,
+    // the indices of every cell's neighbors. They never change, so they are computed once when
+    // the grid is built. The lists are not saved, a loaded grid rebuilds them on first use
+    #[serde(skip)]
+    neighbor_cache: OnceLock<Vec<Vec<usize>>>,
+    // each row as it was last returned by `render_dirty_rows`
+    #[serde(skip)]
+    rendered_rows: Vec<String>,
+}
+
+/// the errors that can occur when building a grid from a text layout
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayoutError {
+    /// the layout does not contain any cells
+    Empty,
+    /// line `row` of the layout has `found` cells, while its first row has `expected` cells. Lines
+    /// are counted from zero, including blank lines
+    RaggedRows {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// the character `ch` at line `row`, column `col` of the layout does not describe a cell
+    UnknownChar { row: usize, col: usize, ch: char },
+    /// the revealed digit `found` at `row`,`col` does not match the `expected` number of
+    /// mines adjacent to the cell
+    WrongCount {
+        row: usize,
+        col: usize,
+        expected: u8,
+        found: u8,
+    },
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            LayoutError::Empty => write!(f, "the layout is empty"),
+            LayoutError::RaggedRows {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {} of the layout has {} cells, expected {}",
+                row, found, expected
+            ),
+            LayoutError::UnknownChar { row, col, ch } => write!(
+                f,
+                "unknown layout character '{}' at line {} column {}",
+                ch, row, col
+            ),
+            LayoutError::WrongCount {
+                row,
+                col,
+                expected,
+                found,
+            } => write!(
+                f,
+                "the cell at row {} column {} shows {} but has {} adjacent mines",
+                row, col, found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LayoutError {}
+
+/// the errors that can occur when building a grid from a board code
+#[derive(Debug, Clone, PartialEq)]
+pub enum CodeError {
+    /// the code is not of the form `<rows>x<cols>.<mines>`, or a dimension is zero
+    Malformed,
+    /// the character `ch` is not part of the code alphabet
+    UnknownChar(char),
+    /// the code holds `found` characters of mines, while the grid needs `expected`
+    WrongLength { expected: usize, found: usize },
+}
+
+impl fmt::Display for CodeError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            CodeError::Malformed => write!(f, "the code is not of the form <rows>x<cols>.<mines>"),
+            CodeError::UnknownChar(ch) => write!(f, "unknown code character '{}'", ch),
+            CodeError::WrongLength { expected, found } => write!(
+                f,
+                "the code has {} characters of mines, expected {}",
+                found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CodeError {}
+
+/// statistics describing how a game of minesweeper was played
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameSummary {
+    pub board_3bv: usize,
+    pub clicks: usize,
+    pub efficiency: f64,
+    pub elapsed_seconds: u64,
+    pub free_moves: usize,
+    pub safe_assists: usize,
+    pub correct_flags: usize,
+    pub incorrect_flags: usize,
+    pub questions_used: usize,
+    pub questions_enabled: bool,
+    pub xray_used: bool,
+    pub flags_enabled: bool,
+    pub lost_on_time: bool,
+}
+
+impl fmt::Display for GameSummary {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "time: {}s, 3BV: {}, clicks: {}, efficiency: {:.2}, ",
+            self.elapsed_seconds, self.board_3bv, self.clicks, self.efficiency
+        )?;
+        write!(
+            f,
+            "free moves: {}, safe assists: {}, ",
+            self.free_moves, self.safe_assists
+        )?;
+        if self.flags_enabled {
+            write!(
+                f,
+                "flags: {} correct, {} incorrect, ",
+                self.correct_flags, self.incorrect_flags
+            )?;
+        }
+        if self.questions_enabled {
+            write!(f, "question marks: {}", self.questions_used)?;
+        } else {
+            write!(f, "question marks: forbidden")?;
+        }
+        if self.xray_used {
+            write!(f, ", x-ray used")?;
+        }
+        if !self.flags_enabled {
+            write!(f, ", no flags")?;
+        }
+        if self.lost_on_time {
+            write!(f, ", out of time")?;
+        }
+        Ok(())
+    }
+}
+
+/// (de)serializes the cell notes as a list of (index, note) pairs, since most formats only
+/// support string map keys
+mod note_pairs {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+
+    pub fn serialize<S: Serializer>(
+        notes: &HashMap<(usize, usize), String>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut pairs = notes.iter().collect::<Vec<_>>();
+        pairs.sort();
+        pairs.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<(usize, usize), String>, D::Error> {
+        let pairs = Vec::<((usize, usize), String)>::deserialize(deserializer)?;
+        Ok(pairs.into_iter().collect())
+    }
 }
 
 impl Cell {
@@ -34,6 +301,21 @@ impl Cell {
             adj_mine_count: 0,
         }
     }
+
+    /// returns the character used to draw this cell with the given `theme`, taking into account
+    /// whether or not the cell has been revealed or marked
+    pub fn glyph(&self, theme: &Theme) -> char {
+        match self.state {
+            CellState::Revealed => match self.kind {
+                CellKind::Mine => theme.mine,
+                CellKind::Empty if self.adj_mine_count > 0 => (self.adj_mine_count + 48) as char,
+                _ => theme.revealed,
+            },
+            CellState::Marked(CellMarker::Flagged) => theme.flag,
+            CellState::Marked(CellMarker::Questioned) => theme.question,
+            CellState::Hidden => theme.hidden,
+        }
+    }
 }
 
 impl MineSweeperCell for Cell {
@@ -86,17 +368,7 @@ impl MineSweeperCell for Cell {
 /// This method is used to display the gridCell during a game of MineSweeper
 impl fmt::Display for Cell {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        let cell_char = match self.state {
-            CellState::Revealed => match self.kind {
-                CellKind::Mine => MINE,
-                CellKind::Empty if self.adj_mine_count > 0 => (self.adj_mine_count + 48) as char,
-                _ => REVEALED,
-            },
-            CellState::Marked(CellMarker::Flagged) => FLAG,
-            CellState::Marked(CellMarker::Questioned) => QUESTION,
-            CellState::Hidden => HIDDEN,
-        };
-        write!(f, "{}", cell_char)
+        write!(f, "{}", self.glyph(&Theme::default()))
     }
 }
 
@@ -122,43 +394,225 @@ impl Grid<Cell> {
         grid
     }
 
-    /// Generates `count` amount of random grid indices and returns them in a Vector<usize>
-    fn gen_rand_grid_indices(row_len: usize, col_len: usize, count: usize) -> Vec<usize> {
+    /// Generates `count` amount of random grid indices, drawn from `rng`, and returns them in a
+    /// Vector<usize>
+    fn gen_rand_grid_indices<R: Rng>(
+        rng: &mut R,
+        row_len: usize,
+        col_len: usize,
+        count: usize,
+    ) -> Vec<usize> {
         // build a vec of all grid indices in row major form and shuffle them
-        let mut rng = thread_rng();
         let mut grid_indices: Vec<usize> = (0..(row_len * col_len)).collect();
-        grid_indices.shuffle(&mut rng);
+        grid_indices.shuffle(rng);
         grid_indices.into_iter().take(count).collect()
     }
 
-    /// returns the **indices** of all grid cells "adjacent" to the cell located at `index`, but
-    /// does not include the cell at `index`
-    fn adjacent_indices(num_rows: usize, num_cols: usize, index: usize) -> Vec<usize> {
-        let mut adj_ndxs = vec![];
-        let r = index / num_cols;
-        let c = index % num_cols;
-        let rstart = if r <= 1 { 0 } else { r - 1 };
-        let cstart = if c <= 1 { 0 } else { c - 1 };
-        let rend = if (r + 1) >= num_rows {
-            num_rows - 1
-        } else {
-            r + 1
-        };
-        let cend = if (c + 1) >= num_cols {
-            num_cols - 1
-        } else {
-            c + 1
-        };
+    /// Generates `count` amount of grid indices, drawn from `rng`, that are grouped together in
+    /// clusters. Each new mine usually grows from a neighbor of an already placed mine, otherwise
+    /// it starts a new cluster at a random location
+    fn gen_clustered_grid_indices<R: Rng>(
+        rng: &mut R,
+        row_len: usize,
+        col_len: usize,
+        count: usize,
+    ) -> Vec<usize> {
+        let mut mined = vec![false; row_len * col_len];
+        let mut mine_ndxs: Vec<usize> = Vec::with_capacity(count);
 
-        for nr in rstart..=rend {
-            for nc in cstart..=cend {
-                // push all the cells located around index: r,c  into the return vector
-                if !(nr == r && nc == c) {
-                    adj_ndxs.push(nr * num_cols + nc);
+        while mine_ndxs.len() < count.min(row_len * col_len) {
+            let grown = match mine_ndxs.choose(rng) {
+                Some(&ndx) if rng.gen_bool(CLUSTER_GROWTH) => {
+                    Grid::adjacent_indices(row_len, col_len, ndx)
+                        .into_iter()
+                        .filter(|adj_ndx| !mined[*adj_ndx])
+                        .collect::<Vec<usize>>()
+                        .choose(rng)
+                        .copied()
                 }
+                _ => None,
+            };
+            let index = grown.unwrap_or_else(|| {
+                let unmined = (0..mined.len())
+                    .filter(|ndx| !mined[*ndx])
+                    .collect::<Vec<usize>>();
+                unmined[rng.gen_range(0, unmined.len())]
+            });
+            mined[index] = true;
+            mine_ndxs.push(index);
+        }
+        mine_ndxs
+    }
+
+    /// initialize a new grid with r rows and c columns, spreading the mines across the grid
+    /// according to the given `distribution`
+    pub fn init_with_distribution(
+        num_rows: usize,
+        num_cols: usize,
+        distribution: MineDistribution,
+    ) -> Self {
+        match distribution {
+            MineDistribution::Uniform => {
+                Grid::init_with_seed(num_rows, num_cols, thread_rng().gen())
+            }
+            MineDistribution::Clustered => {
+                let total_mines = Grid::mines_for_density(num_rows, num_cols, 0.15);
+                let mine_ndxs = Grid::gen_clustered_grid_indices(
+                    &mut thread_rng(),
+                    num_rows,
+                    num_cols,
+                    total_mines,
+                );
+                Grid::with_mines(num_rows, num_cols, &mine_ndxs)
+            }
+        }
+    }
+
+    /// initialize a new grid with r rows and c columns, with mines spread uniformly across 15%
+    /// of the cells by an rng seeded with `seed`. The same seed and dimensions always build the
+    /// same grid, so a board can be reproduced or shared by its seed
+    pub fn init_with_seed(num_rows: usize, num_cols: usize, seed: u64) -> Self {
+        let total_mines = Grid::mines_for_density(num_rows, num_cols, 0.15);
+        Grid::init_seeded(num_rows, num_cols, total_mines, seed)
+    }
+
+    /// initialize a new grid with r rows and c columns holding exactly `mines` mines, spread
+    /// uniformly across the grid. The count is capped at the number of cells
+    pub fn init_with_mine_count(num_rows: usize, num_cols: usize, mines: usize) -> Self {
+        let mines = mines.min(num_rows * num_cols);
+        let mine_ndxs = Grid::gen_rand_grid_indices(&mut thread_rng(), num_rows, num_cols, mines);
+        Grid::with_mines(num_rows, num_cols, &mine_ndxs)
+    }
+
+    /// like `init_with_mine_count`, but the mines are placed by an rng seeded with `seed`, so the
+    /// same seed and dimensions always build the same grid. The seed is kept with the grid
+    pub fn init_seeded(num_rows: usize, num_cols: usize, mines: usize, seed: u64) -> Self {
+        let mines = mines.min(num_rows * num_cols);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mine_ndxs = Grid::gen_rand_grid_indices(&mut rng, num_rows, num_cols, mines);
+        let mut grid = Grid::with_mines(num_rows, num_cols, &mine_ndxs);
+        grid.seed = Some(seed);
+        grid
+    }
+
+    /// returns the fraction of the cells of a `rows` x `cols` grid that `mines` mines cover. This
+    /// is the inverse of `mines_for_density`, a grid without cells has a density of 0
+    pub fn density_for_mines(rows: usize, cols: usize, mines: usize) -> f32 {
+        if rows * cols == 0 {
+            return 0.0;
+        }
+        mines as f32 / (rows * cols) as f32
+    }
+
+    /// returns the number of mines that cover `density` of the cells of a `rows` x `cols` grid,
+    /// rounded to the nearest mine and capped at the number of cells
+    pub fn mines_for_density(rows: usize, cols: usize, density: f32) -> usize {
+        let mines = ((rows * cols) as f32 * density).round() as usize;
+        mines.min(rows * cols)
+    }
+
+    /// initialize a new grid with r rows and c columns holding `mines` mines, whose 3BV falls
+    /// within `min_3bv..=max_3bv`. Grids are generated from `seed` until one is in range, giving
+    /// up after a fixed number of attempts and returning the attempt whose 3BV came closest
+    pub fn init_with_3bv_range(
+        num_rows: usize,
+        num_cols: usize,
+        mines: usize,
+        min_3bv: usize,
+        max_3bv: usize,
+        seed: u64,
+    ) -> Self {
+        let mines = mines.min(num_rows * num_cols);
+        let mut rng = StdRng::seed_from_u64(seed);
+        // how far the 3BV of a grid falls outside of the range
+        let distance = |grid: &Self| {
+            let board_3bv = grid.board_3bv();
+            min_3bv.saturating_sub(board_3bv) + board_3bv.saturating_sub(max_3bv)
+        };
+
+        let mut closest: Option<(Self, usize)> = None;
+        for _attempt in 0..MAX_3BV_ATTEMPTS {
+            let mine_ndxs = Grid::gen_rand_grid_indices(&mut rng, num_rows, num_cols, mines);
+            let mut grid = Grid::with_mines(num_rows, num_cols, &mine_ndxs);
+            grid.seed = Some(seed);
+            let grid_distance = distance(&grid);
+            if grid_distance == 0 {
+                return grid;
             }
+            if closest
+                .as_ref()
+                .is_none_or(|(_closest, closest_distance)| grid_distance < *closest_distance)
+            {
+                closest = Some((grid, grid_distance));
+            }
+        }
+        closest
+            .map(|(grid, _distance)| grid)
+            .unwrap_or_else(|| Grid::with_mines(num_rows, num_cols, &[]))
+    }
+
+    /// initialize a new grid with r rows and c columns whose mines are placed with 180 degree
+    /// rotational symmetry, every mine is paired with the mine in its rotated position. The
+    /// same `seed` always produces the same grid.
+    /// On a grid with an odd number of cells, the center cell is its own partner and holds the
+    /// odd mine when `mines` is odd. Otherwise an odd mine has no partner and is left out, so the
+    /// grid may hold one mine less than asked for
+    pub fn init_symmetric(num_rows: usize, num_cols: usize, mines: usize, seed: u64) -> Self {
+        let num_cells = num_rows * num_cols;
+        let mines = mines.min(num_cells);
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        // the cell at 1D index i rotates onto the cell at index num_cells - 1 - i
+        let mut pairs: Vec<usize> = (0..num_cells / 2).collect();
+        pairs.shuffle(&mut rng);
+        let mut mine_ndxs = pairs
+            .into_iter()
+            .take(mines / 2)
+            .flat_map(|ndx| [ndx, num_cells - 1 - ndx])
+            .collect::<Vec<usize>>();
+        if mines % 2 == 1 && num_cells % 2 == 1 {
+            mine_ndxs.push(num_cells / 2);
         }
-        adj_ndxs
+        let mut grid = Grid::with_mines(num_rows, num_cols, &mine_ndxs);
+        grid.seed = Some(seed);
+        grid
+    }
+
+    /// returns the index of the cell offset from `r`,`c` by `dr` rows and `dc` columns, or `None`
+    /// if that cell would fall outside of the grid
+    fn checked_neighbor(
+        num_rows: usize,
+        num_cols: usize,
+        (r, c): (usize, usize),
+        (dr, dc): (isize, isize),
+    ) -> Option<usize> {
+        let nr = r.checked_add_signed(dr).filter(|nr| *nr < num_rows)?;
+        let nc = c.checked_add_signed(dc).filter(|nc| *nc < num_cols)?;
+        Some(nr * num_cols + nc)
+    }
+
+    /// returns the **indices** of all grid cells "adjacent" to the cell located at `index`, but
+    /// does not include the cell at `index`
+    fn adjacent_indices(num_rows: usize, num_cols: usize, index: usize) -> Vec<usize> {
+        let pos = (index / num_cols, index % num_cols);
+        NEIGHBOR_OFFSETS
+            .iter()
+            .filter_map(|offset| Grid::checked_neighbor(num_rows, num_cols, pos, *offset))
+            .collect()
+    }
+
+    /// returns the adjacent indices of every cell on a grid with the given dimensions
+    fn neighbor_lists(num_rows: usize, num_cols: usize) -> Vec<Vec<usize>> {
+        (0..num_rows * num_cols)
+            .map(|index| Grid::adjacent_indices(num_rows, num_cols, index))
+            .collect()
+    }
+
+    /// returns the cached indices of the cells adjacent to the cell at `index`
+    fn adjacent(&self, index: usize) -> &[usize] {
+        &self
+            .neighbor_cache
+            .get_or_init(|| Grid::neighbor_lists(self.num_rows, self.num_cols))[index]
     }
 
     /// returns grid indices that are connected to the cell at `index` AND that
@@ -169,10 +623,8 @@ impl Grid<Cell> {
         let mut to_visit = vec![index]; // cells left to visit
         let mut connected_ndxs = vec![]; // holds the connected cell indices
 
-        while !to_visit.is_empty() {
-            // current index being visited
-            let cur_ndx = to_visit.pop().unwrap();
-
+        // current index being visited
+        while let Some(cur_ndx) = to_visit.pop() {
             if visited.contains(&cur_ndx) {
                 continue;
             } else {
@@ -185,8 +637,10 @@ impl Grid<Cell> {
                 visited.push(cur_ndx);
 
                 // build a list of "lone" cells adjacent to the current cell
-                let mut adj_ndxs = Grid::adjacent_indices(self.num_rows, self.num_cols, cur_ndx)
-                    .into_iter()
+                let mut adj_ndxs = self
+                    .adjacent(cur_ndx)
+                    .iter()
+                    .copied()
                     .filter(|ndx| self.grid[*ndx].is_lone_cell())
                     .collect::<Vec<usize>>();
                 to_visit.append(&mut adj_ndxs);
@@ -195,249 +649,3394 @@ impl Grid<Cell> {
         connected_ndxs
     }
 
+    /// reveals the cell at `index` along with any connected lone cells. The index and previous
+    /// state of every cell that gets revealed is pushed onto `revealed`
+    fn reveal_index(&mut self, index: usize, revealed: &mut Vec<(usize, CellState)>) {
+        if *self.grid[index].state() != CellState::Revealed {
+            revealed.push((index, *self.grid[index].state()));
+            self.grid[index].set_state(CellState::Revealed);
+            self.reveal_lone_cells_from(index, revealed);
+        }
+    }
+
+    /// reveals all lone cells connected to the cell at `index`, plus the cells bordering them
+    fn reveal_lone_cells_from(&mut self, index: usize, revealed: &mut Vec<(usize, CellState)>) {
+        let connected_ndxs = self.connected_lone_cell_indices(index);
+
+        // also reveal all the cells that are adjacent to the lone cells
+        let adj_perimeter_cells: HashSet<usize> = connected_ndxs
+            .iter()
+            .flat_map(|ndx| self.adjacent(*ndx).iter().copied())
+            .collect();
+
+        for ndx in connected_ndxs {
+            self.reveal_index(ndx, revealed);
+        }
+        for ndx in adj_perimeter_cells {
+            self.reveal_index(ndx, revealed);
+        }
+    }
+
+    /// returns `true` if the cell at `index` is a revealed number with exactly as many flags
+    /// around it as adjacent mines
+    fn is_chordable(&self, index: usize) -> bool {
+        let cell = &self.grid[index];
+        if *cell.state() != CellState::Revealed || cell.adj_mine_count() == 0 {
+            return false;
+        }
+        self.adjacent_flag_count(index) == cell.adj_mine_count() as usize
+    }
+
+    /// returns the number of flagged cells adjacent to the cell at `index`
+    fn adjacent_flag_count(&self, index: usize) -> usize {
+        self.adjacent(index)
+            .iter()
+            .filter(|ndx| self.grid[**ndx].is_flagged())
+            .count()
+    }
+
+    /// returns `true` if the cell at `index` is questioned and questioned cells are protected
+    /// from being revealed
+    fn is_protected_question(&self, index: usize) -> bool {
+        self.protect_questioned
+            && *self.grid[index].state() == CellState::Marked(CellMarker::Questioned)
+    }
+
+    /// reveals every cell, that is not flagged (or a protected question), adjacent to the cell
+    /// at `index`
+    fn chord_index(&mut self, index: usize, revealed: &mut Vec<(usize, CellState)>) {
+        let misplaced = self.misplaced_flags(index / self.num_cols, index % self.num_cols);
+        for ndx in self.adjacent(index).to_vec() {
+            if !self.grid[ndx].is_flagged() && !self.is_protected_question(ndx) {
+                self.reveal_index(ndx, revealed);
+                if *self.grid[ndx].kind() == CellKind::Mine && self.detonating_chord.is_none() {
+                    self.detonating_chord = Some((index, misplaced.clone()));
+                }
+            }
+        }
+    }
+
+    /// chords every satisfied number in `revealed`, including the numbers that those chords
+    /// reveal in turn
+    fn auto_chord_revealed(&mut self, revealed: &mut Vec<(usize, CellState)>) {
+        let mut next = 0;
+        while next < revealed.len() {
+            let index = revealed[next].0;
+            if self.is_chordable(index) {
+                self.chord_index(index, revealed);
+            }
+            next += 1;
+        }
+    }
+
+    /// records the cells opened by a single reveal (or chord) so that it can be undone later.
+    /// Returns `true` if any cells were opened
+    fn log_reveal(&mut self, revealed: Vec<(usize, CellState)>) -> bool {
+        if revealed.is_empty() {
+            return false;
+        }
+        let num_cols = self.num_cols;
+        self.reveal_history.extend(
+            revealed
+                .iter()
+                .map(|(ndx, _state)| (ndx / num_cols, ndx % num_cols)),
+        );
+        self.reveal_log.push(revealed);
+        if !self.started {
+            self.started = true;
+            self.timer_start = Some(Instant::now());
+        }
+        self.finish_if_all_safe_revealed();
+        true
+    }
+
+    /// under `RevealAllSafe`, the game is won the moment the last empty cell is revealed, even
+    /// part way through a stepped cascade. The timer is stopped at that moment and any reveals
+    /// still pending are dropped
+    fn finish_if_all_safe_revealed(&mut self) {
+        if self.win_condition() == WinCondition::RevealAllSafe
+            && self.remaining_safe_cells() == 0
+            && !self.is_game_lost()
+        {
+            self.pause_timer();
+            self.pending_reveals.clear();
+        }
+    }
+
+    /// moves the mines in the cell at `index` and its neighbors to random cells outside of them,
+    /// keeping the number of mines. If the grid is too small to keep the neighbors clear, only
+    /// the cell itself is cleared. The mines are moved the same way every time for the same grid,
+    /// driven by its seed, or by its `layout_hash` if it was not generated from a seed
+    fn clear_mines_around(&mut self, index: usize) {
+        let mut cleared = vec![index];
+        cleared.extend_from_slice(self.adjacent(index));
+        if self.grid.len() - cleared.len() < self.num_mines {
+            cleared.truncate(1);
+        }
+        if self.grid.len() - cleared.len() < self.num_mines {
+            return;
+        }
+
+        let mined = |grid: &Self, ndx: usize| *grid.grid[ndx].kind() == CellKind::Mine;
+        let moved = cleared.iter().filter(|ndx| mined(self, **ndx)).count();
+        if moved == 0 {
+            return;
+        }
+        let mut mine_ndxs = (0..self.grid.len())
+            .filter(|ndx| mined(self, *ndx) && !cleared.contains(ndx))
+            .collect::<Vec<usize>>();
+        let mut candidates = (0..self.grid.len())
+            .filter(|ndx| !mined(self, *ndx) && !cleared.contains(ndx))
+            .collect::<Vec<usize>>();
+        let mut rng = StdRng::seed_from_u64(self.seed.unwrap_or_else(|| self.layout_hash()));
+        candidates.shuffle(&mut rng);
+        mine_ndxs.extend(candidates.into_iter().take(moved));
+        self.set_mines(&mine_ndxs);
+    }
+
+    /// places mines at the given (1D) `mine_ndxs`, every other cell is made empty, and
+    /// recomputes the adjacent mine counts of every cell
+    fn set_mines(&mut self, mine_ndxs: &[usize]) {
+        for cell in self.grid.iter_mut() {
+            cell.set_kind(CellKind::Empty);
+            cell.set_adj_mine_count(0);
+        }
+        for index in mine_ndxs.iter().copied() {
+            self.grid[index].set_kind(CellKind::Mine);
+            for adj_ndx in self.adjacent(index).to_vec() {
+                let cur_count = self.grid[adj_ndx].adj_mine_count() + 1;
+                self.grid[adj_ndx].set_adj_mine_count(cur_count);
+            }
+        }
+        self.num_mines = mine_ndxs.len();
+    }
+
     /// translates a two-dimensional row, column index into a one-dimensional index
     fn to_1d(&self, row: usize, column: usize) -> usize {
         row * self.num_cols + column
     }
-}
 
-impl MineSweeperGame for Grid<Cell> {
-    fn init(num_rows: usize, num_cols: usize) -> Self {
+    /// builds a grid with mines placed at the given (1D) `mine_ndxs` and computes the adjacent
+    /// mine counts of every cell
+    pub(crate) fn with_mines(num_rows: usize, num_cols: usize, mine_ndxs: &[usize]) -> Self {
         let mut grid = Grid::empty_grid(num_rows, num_cols);
-
-        // generate random mine locations
-        let total_mines = ((num_rows * num_cols) as f32 * 0.15f32).round() as usize;
-        let mine_ndxs = Grid::gen_rand_grid_indices(num_rows, num_cols, total_mines);
         for index in mine_ndxs.iter() {
             grid[*index] = Cell::new(CellKind::Mine);
         }
 
+        let neighbor_cache = Grid::neighbor_lists(num_rows, num_cols);
+
         // compute the adjacent mine counts for every cell that contains a mine
         for index in mine_ndxs.iter() {
-            for adj_ndx in Grid::adjacent_indices(num_rows, num_cols, *index) {
+            for adj_ndx in neighbor_cache[*index].iter().copied() {
                 let cur_count = grid[adj_ndx].adj_mine_count() + 1;
                 grid[adj_ndx].set_adj_mine_count(cur_count);
             }
         }
 
+        let num_mines = grid
+            .iter()
+            .filter(|cell| *cell.kind() == CellKind::Mine)
+            .count();
+
         Grid {
             grid,
             num_rows,
             num_cols,
+            num_mines,
+            seed: None,
+            detonating_chord: None,
+            training_mode: false,
+            solution_limit: DEFAULT_SOLUTION_LIMIT,
+            reveal_log: vec![],
+            reveal_history: vec![],
+            pending_reveals: vec![],
+            started: false,
+            clicks: 0,
+            chord_clicks: 0,
+            chord_reveals: 0,
+            notes: HashMap::new(),
+            free_moves: 0,
+            safe_assists: 0,
+            theme: Theme::default(),
+            elapsed_time: Duration::ZERO,
+            timer_start: None,
+            questions_enabled: true,
+            questions_used: 0,
+            flags_enabled: true,
+            auto_chord: false,
+            win_condition: WinCondition::FlagAllMines,
+            xray: false,
+            xray_used: false,
+            lost_on_time: false,
+            protect_questioned: false,
+            neighbor_cache: OnceLock::from(neighbor_cache),
+            rendered_rows: vec![],
         }
     }
 
-    fn dimensions(&self) -> (usize, usize) {
-        (self.num_rows, self.num_cols)
+    /// returns the seed the mines were placed from, or `None` if the grid was not generated from
+    /// a seed. The seed alone does not rebuild the board: the generator and mine count it was
+    /// used with are needed as well, and a safe first reveal may have moved mines since. See
+    /// `reproducible_seed`
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
     }
 
-    fn mine_indices(&self) -> Vec<(usize, usize)> {
-        self.grid
-            .iter()
-            .enumerate()
-            .filter(|(_ndx, cell)| *cell.kind() == CellKind::Mine)
-            .map(|(ndx, _cell)| (ndx / self.num_cols, ndx % self.num_cols))
-            .collect::<Vec<(usize, usize)>>()
+    /// returns the seed if `init_with_seed`, given the seed and the dimensions of the grid,
+    /// builds a board with the same mines as this one. Sharing such a seed and the dimensions
+    /// lets another player play the same board, otherwise `to_code` has to be shared instead
+    pub fn reproducible_seed(&self) -> Option<u64> {
+        let seed = self.seed?;
+        let rebuilt = Grid::init_with_seed(self.num_rows, self.num_cols, seed);
+        if rebuilt.mine_indices() == self.mine_indices() {
+            Some(seed)
+        } else {
+            None
+        }
     }
 
-    /// The number of total mines on a grid is set using the following:
-    /// TOTAL_MINES = grid.rows * grid.columns * 0.15
-    fn total_mines(&self) -> usize {
-        ((self.num_rows * self.num_cols) as f32 * 0.15f32).round() as usize
+    /// when set, questioned cells are protected like flagged ones: `reveal_cell` leaves them
+    /// alone and chording skips them. Off by default
+    pub fn set_protect_questioned(&mut self, protect_questioned: bool) {
+        self.protect_questioned = protect_questioned;
     }
 
-    fn reveal_cell(&mut self, r: usize, c: usize) {
-        let index = self.to_1d(r, c);
-        if *self.grid[index].state() != CellState::Revealed {
-            self.grid[index].set_state(CellState::Revealed);
-            self.reveal_all_lone_cells(r, c);
-        }
+    /// returns `true` if questioned cells are protected from being revealed
+    pub fn protect_questioned(&self) -> bool {
+        self.protect_questioned
     }
 
-    fn reveal_all_lone_cells(&mut self, r: usize, c: usize) {
-        let connected_ndxs = self.connected_lone_cell_indices(self.to_1d(r, c));
+    /// turns x-ray on or off. While x-ray is on, mines that are not revealed or flagged are drawn
+    /// with a marker. This is a practice aid, turning it on is recorded in the game summary
+    pub fn set_xray(&mut self, xray: bool) {
+        self.xray = xray;
+        self.xray_used |= xray;
+    }
 
-        // also reveal all the cells that are adjacent to the lone cells
-        let adj_perimeter_cells: HashSet<usize> = connected_ndxs
-            .iter()
-            .flat_map(|ndx| Grid::adjacent_indices(self.num_rows, self.num_cols, *ndx))
-            .collect();
+    /// returns `true` if x-ray is on
+    pub fn xray(&self) -> bool {
+        self.xray
+    }
 
-        for ndx in connected_ndxs {
-            self.reveal_cell(ndx / self.num_cols, ndx % self.num_cols);
-        }
-        for ndx in adj_perimeter_cells {
-            self.reveal_cell(ndx / self.num_cols, ndx % self.num_cols);
+    /// returns the character the cell at `r`,`c` is drawn with, using the grid's theme. While
+    /// x-ray is on, hidden and questioned mines are drawn as `XRAY`
+    pub fn glyph(&self, r: usize, c: usize) -> char {
+        let cell = &self.grid[self.to_1d(r, c)];
+        if self.xray
+            && *cell.kind() == CellKind::Mine
+            && *cell.state() != CellState::Revealed
+            && !cell.is_flagged()
+        {
+            XRAY
+        } else {
+            cell.glyph(&self.theme)
         }
     }
 
-    fn flag_cell(&mut self, r: usize, c: usize) {
-        let index = self.to_1d(r, c);
-        if *self.grid[index].state() != CellState::Revealed {
-            self.grid[index].set_state(CellState::Marked(CellMarker::Flagged));
+    /// returns whether the game is in progress, won or lost. A revealed mine loses the game,
+    /// even if the win condition is met as well
+    pub fn game_state(&self) -> GameState {
+        if self.is_game_lost() {
+            GameState::Lost
+        } else if self.is_game_won() {
+            GameState::Won
+        } else {
+            GameState::InProgress
         }
     }
 
-    fn question_cell(&mut self, r: usize, c: usize) {
-        let index = self.to_1d(r, c);
-        if *self.grid[index].state() != CellState::Revealed {
-            self.grid[index].set_state(CellState::Marked(CellMarker::Questioned));
+    /// reveals the cells of `path` in order, stopping early once the game is won or lost, and
+    /// returns the state of the game afterwards
+    pub fn reveal_path(&mut self, path: &[(usize, usize)]) -> GameState {
+        for (r, c) in path {
+            if self.game_state() != GameState::InProgress {
+                break;
+            }
+            self.reveal_cell(*r, *c);
         }
+        self.game_state()
     }
 
-    fn unmark_cell(&mut self, r: usize, c: usize) {
-        let index = self.to_1d(r, c);
-        if *self.grid[index].state() != CellState::Revealed {
-            self.grid[index].set_state(CellState::Hidden);
+    /// sets what a player must do to win the game. Without flags, the game can only be won by
+    /// revealing every empty cell, so `FlagAllMines` is ignored while flags are disabled
+    pub fn set_win_condition(&mut self, win_condition: WinCondition) {
+        if self.flags_enabled {
+            self.win_condition = win_condition;
         }
     }
 
-    fn toggle_mark(&mut self, r: usize, c: usize, mark: CellMarker) {
-        let index = self.to_1d(r, c);
-        if let CellState::Marked(_) = self.grid[index].state() {
-            self.unmark_cell(r, c);
-        } else {
-            self.grid[index].set_state(CellState::Marked(mark));
+    /// enables or disables flags and question marks. Disabling them is a hardcore mode where
+    /// every marker method does nothing, and the win condition becomes `RevealAllSafe`
+    pub fn set_flags_enabled(&mut self, enabled: bool) {
+        self.flags_enabled = enabled;
+        if !enabled {
+            self.win_condition = WinCondition::RevealAllSafe;
         }
     }
 
-    fn is_game_won(&self) -> bool {
-        self.mine_indices().iter().all(|(r, c)| {
-            let index = self.to_1d(*r, *c);
-            self.grid[index].is_flagged()
-        })
+    /// returns `true` if cells can be flagged or questioned
+    pub fn flags_enabled(&self) -> bool {
+        self.flags_enabled
     }
 
-    fn is_game_lost(&self) -> bool {
-        self.mine_indices().iter().any(|(r, c)| {
-            let index = self.to_1d(*r, *c);
-            *self.grid[index].state() == CellState::Revealed
-        })
+    /// returns what a player must do to win the game
+    pub fn win_condition(&self) -> WinCondition {
+        self.win_condition
     }
-}
 
-impl fmt::Display for Grid<Cell> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut buf = String::new();
-        for ri in 0..self.num_rows {
-            for ci in 0..self.num_cols {
-                let index = self.to_1d(ri, ci);
-                buf.push_str(format!(" {}", self.grid[index]).as_str());
-            }
-            buf.push('\n')
-        }
-        write!(f, "{}", buf)
+    /// returns `true` if the grid has no mines, such a game is won by its first reveal
+    pub fn is_trivial(&self) -> bool {
+        self.num_mines == 0
     }
-}
 
-impl fmt::Debug for Grid<Cell> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut buf = String::new();
-        for ri in 0..self.num_rows {
-            for ci in 0..self.num_cols {
-                let index = self.to_1d(ri, ci);
-                buf.push_str(format!(" {:?}", self.grid[index]).as_str());
-            }
-            buf.push('\n')
-        }
-        write!(f, "{}", buf)
+    /// returns the number of empty cells that have not been revealed yet
+    pub fn remaining_safe_cells(&self) -> usize {
+        self.grid
+            .iter()
+            .filter(|cell| *cell.kind() == CellKind::Empty && *cell.state() != CellState::Revealed)
+            .count()
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::mine_sweeper_board::{
-        CellKind, CellMarker, CellState, MineSweeperCell, FLAG, HIDDEN, MINE, QUESTION, REVEALED,
-    };
-    use crate::mine_sweeper_impl::Cell;
+    /// returns `true` if every empty cell has been revealed, the standard way to win a game of
+    /// minesweeper where flags are optional. This holds whatever the grid's win condition is,
+    /// `is_game_won` checks the win condition that is set
+    pub fn is_game_won_by_reveal(&self) -> bool {
+        self.remaining_safe_cells() == 0
+    }
 
-    #[test]
-    fn revealed_mined_cell_should_display_as_mine_char() {
-        let mined_cell = Cell {
-            state: CellState::Revealed,
-            kind: CellKind::Mine,
-            adj_mine_count: 0,
-        };
-        assert_eq!(format!("{}", mined_cell), MINE.to_string());
+    /// enables or disables auto chording. When enabled, every satisfied number opened by a
+    /// reveal is chorded automatically, which can lose the game if flags are misplaced
+    pub fn set_auto_chord(&mut self, auto_chord: bool) {
+        self.auto_chord = auto_chord;
     }
 
-    #[test]
-    fn hidden_cell_should_display_as_hidden_char() {
-        let mined_cell = Cell {
-            state: CellState::Hidden,
-            kind: CellKind::Mine,
-            adj_mine_count: 0,
+    /// returns `true` if auto chording is enabled
+    pub fn auto_chord(&self) -> bool {
+        self.auto_chord
+    }
+
+    /// allows or forbids placing question marks. When forbidden, `question_cell` does nothing
+    /// and `try_question_cell` returns an error
+    pub fn set_questions_enabled(&mut self, enabled: bool) {
+        self.questions_enabled = enabled;
+    }
+
+    /// returns `true` if question marks may be placed
+    pub fn questions_enabled(&self) -> bool {
+        self.questions_enabled
+    }
+
+    /// places a question mark at `r`,`c` like `question_cell`, but returns a `QuestionsForbidden`
+    /// error instead of ignoring it when question marks (or all markers) are forbidden, so the
+    /// player can be told why nothing happened
+    pub fn try_question_cell(&mut self, r: usize, c: usize) -> Result<(), MinesweeperError> {
+        self.checked_index(r, c)?;
+        if !self.flags_enabled || !self.questions_enabled {
+            return Err(MinesweeperError::QuestionsForbidden { r, c });
+        }
+        self.question_cell(r, c);
+        Ok(())
+    }
+
+    /// returns the number of question marks placed this game
+    pub fn questions_used(&self) -> usize {
+        self.questions_used
+    }
+
+    /// returns a summary of how the game has been played so far
+    pub fn summary(&self) -> GameSummary {
+        GameSummary {
+            board_3bv: self.board_3bv(),
+            clicks: self.clicks,
+            efficiency: self.efficiency(),
+            elapsed_seconds: self.elapsed_seconds(),
+            free_moves: self.free_moves,
+            safe_assists: self.safe_assists,
+            correct_flags: self.correct_flags(),
+            incorrect_flags: self.incorrect_flags(),
+            questions_used: self.questions_used,
+            questions_enabled: self.questions_enabled,
+            xray_used: self.xray_used,
+            flags_enabled: self.flags_enabled,
+            lost_on_time: self.lost_on_time,
+        }
+    }
+
+    /// returns the number of whole seconds played. The timer starts with the first reveal
+    pub fn elapsed_seconds(&self) -> u64 {
+        self.elapsed_at(Instant::now()).as_secs()
+    }
+
+    /// returns the time played as of the instant `now`, which lets callers measure the game
+    /// against a clock of their own
+    pub fn elapsed_at(&self, now: Instant) -> Duration {
+        let running = self
+            .timer_start
+            .map(|start| now.saturating_duration_since(start))
+            .unwrap_or_default();
+        self.elapsed_time + running
+    }
+
+    /// stops the timer, keeping the time played so far in `elapsed_time` so that it is
+    /// preserved when the grid is saved
+    pub fn pause_timer(&mut self) {
+        if let Some(start) = self.timer_start.take() {
+            self.elapsed_time += start.elapsed();
+        }
+    }
+
+    /// ends the game as a loss because a time limit ran out. The timer is stopped and any
+    /// reveals still pending are dropped
+    pub fn lose_on_time(&mut self) {
+        self.lost_on_time = true;
+        self.pause_timer();
+        self.pending_reveals.clear();
+    }
+
+    /// returns `true` if the game was lost by running out of time
+    pub fn lost_on_time(&self) -> bool {
+        self.lost_on_time
+    }
+
+    /// restarts the timer of a started game, e.g. after it has been loaded, so that timing
+    /// continues from the saved `elapsed_time` rather than from zero
+    pub fn resume_timer(&mut self) {
+        if self.started && self.timer_start.is_none() {
+            match Instant::now().checked_sub(self.elapsed_time) {
+                Some(start) => {
+                    self.timer_start = Some(start);
+                    self.elapsed_time = Duration::ZERO;
+                }
+                // the clock can't be offset that far back, so count on top of the saved time
+                None => self.timer_start = Some(Instant::now()),
+            }
+        }
+    }
+
+    /// returns the theme used to draw the grid
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// sets the theme used to draw the grid
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    /// an assist that reveals a random hidden cell that does not contain a mine. Returns the
+    /// row,col index of the revealed cell, or `None` if every safe cell has already been revealed
+    pub fn reveal_random_safe(&mut self) -> Option<(usize, usize)> {
+        let safe_ndxs = (0..self.grid.len())
+            .filter(|ndx| {
+                *self.grid[*ndx].kind() == CellKind::Empty
+                    && *self.grid[*ndx].state() != CellState::Revealed
+            })
+            .collect::<Vec<usize>>();
+        let index = *safe_ndxs.choose(&mut thread_rng())?;
+        let (r, c) = (index / self.num_cols, index % self.num_cols);
+        self.reveal_cell(r, c);
+        self.free_moves += 1;
+        Some((r, c))
+    }
+
+    /// an assist that reveals the hidden cell least likely to hold a mine, as chosen by
+    /// `safest_cell`. The cell is revealed by `first_reveal`, so it is never a mine on the first
+    /// move. Returns the row,col index of the revealed cell along with the probability that it
+    /// held a mine, or `None` if there is no hidden cell left to reveal, or the probabilities
+    /// could not be worked out within the solution limit
+    pub fn reveal_safest(&mut self) -> Option<((usize, usize), f64)> {
+        let (pos, probability) = self.safest_cell_with_probability()?;
+        self.first_reveal(pos.0, pos.1);
+        self.safe_assists += 1;
+        Some((pos, probability))
+    }
+
+    /// returns the number of safe assists (reveals of the safest cell) used this game
+    pub fn safe_assists(&self) -> usize {
+        self.safe_assists
+    }
+
+    /// builds a new grid from a text layout, one line per row, where `*` is a mine and `.` is
+    /// an empty cell. Every cell starts out hidden
+    /// # Examples
+    /// ```text
+    /// *..
+    /// ..*
+    /// ```
+    pub fn from_layout(layout: &str) -> Result<Self, LayoutError> {
+        Grid::parse_layout(layout, |ch| match ch {
+            '*' => Some((CellKind::Mine, CellState::Hidden)),
+            '.' => Some((CellKind::Empty, CellState::Hidden)),
+            _ => None,
+        })
+    }
+
+    /// builds a grid from a text layout that also holds the state of play, one line per row:
+    /// * `.` hidden empty cell, `*` hidden mine
+    /// * `f` flagged empty cell, `F` flagged mine
+    /// * `q` questioned empty cell, `Q` questioned mine
+    /// * `0`-`8` revealed empty cell, `X` revealed mine
+    ///
+    /// The adjacent mine counts are computed from the mines, a digit that does not match them is
+    /// rejected with `LayoutError::WrongCount`
+    pub fn from_state_layout(layout: &str) -> Result<Self, LayoutError> {
+        let mut grid = Grid::parse_layout(layout, |ch| match ch {
+            '.' => Some((CellKind::Empty, CellState::Hidden)),
+            '*' => Some((CellKind::Mine, CellState::Hidden)),
+            'f' => Some((CellKind::Empty, CellState::Marked(CellMarker::Flagged))),
+            'F' => Some((CellKind::Mine, CellState::Marked(CellMarker::Flagged))),
+            'q' => Some((CellKind::Empty, CellState::Marked(CellMarker::Questioned))),
+            'Q' => Some((CellKind::Mine, CellState::Marked(CellMarker::Questioned))),
+            '0'..='8' => Some((CellKind::Empty, CellState::Revealed)),
+            'X' => Some((CellKind::Mine, CellState::Revealed)),
+            _ => None,
+        })?;
+        for (row, (_line_no, line)) in Grid::layout_rows(layout).iter().enumerate() {
+            for (col, ch) in line.chars().enumerate() {
+                if let Some(found) = ch.to_digit(10) {
+                    let expected = grid.cell(row, col).adj_mine_count();
+                    if found as u8 != expected {
+                        return Err(LayoutError::WrongCount {
+                            row,
+                            col,
+                            expected,
+                            found: found as u8,
+                        });
+                    }
+                }
+            }
+        }
+        grid.started = grid
+            .grid
+            .iter()
+            .any(|cell| *cell.state() == CellState::Revealed);
+        Ok(grid)
+    }
+
+    /// returns the rows of a text layout, each with its line number, with surrounding whitespace
+    /// and blank lines removed
+    fn layout_rows(layout: &str) -> Vec<(usize, &str)> {
+        layout
+            .lines()
+            .map(|line| line.trim())
+            .enumerate()
+            .filter(|(_line_no, line)| !line.is_empty())
+            .collect()
+    }
+
+    /// builds a grid from a text layout, `parse_cell` maps each character to the kind and state
+    /// of its cell, returning `None` for characters it doesn't recognize
+    fn parse_layout<F>(layout: &str, parse_cell: F) -> Result<Self, LayoutError>
+    where
+        F: Fn(char) -> Option<(CellKind, CellState)>,
+    {
+        let rows = Grid::layout_rows(layout);
+        let num_cols = rows.first().ok_or(LayoutError::Empty)?.1.chars().count();
+        if let Some((line_no, line)) = rows
+            .iter()
+            .find(|(_line_no, line)| line.chars().count() != num_cols)
+        {
+            return Err(LayoutError::RaggedRows {
+                row: *line_no,
+                expected: num_cols,
+                found: line.chars().count(),
+            });
+        }
+
+        let cells = rows
+            .iter()
+            .flat_map(|(line_no, line)| {
+                line.chars()
+                    .enumerate()
+                    .map(move |(col, ch)| (*line_no, col, ch))
+            })
+            .map(|(row, col, ch)| parse_cell(ch).ok_or(LayoutError::UnknownChar { row, col, ch }))
+            .collect::<Result<Vec<(CellKind, CellState)>, LayoutError>>()?;
+        let mine_ndxs = cells
+            .iter()
+            .enumerate()
+            .filter(|(_ndx, (kind, _state))| *kind == CellKind::Mine)
+            .map(|(ndx, _cell)| ndx)
+            .collect::<Vec<usize>>();
+
+        let mut grid = Grid::with_mines(rows.len(), num_cols, &mine_ndxs);
+        for (cell, (_kind, state)) in grid.grid.iter_mut().zip(cells) {
+            cell.set_state(state);
+        }
+        Ok(grid)
+    }
+
+    /// returns a compact, single line code of the mine layout, suitable for sharing in a URL or
+    /// a chat. The code holds the dimensions followed by the mines packed six cells to a
+    /// character, e.g. `3x3.BE` for mines in the top left and bottom right corners
+    pub fn to_code(&self) -> String {
+        let mut code = format!("{}x{}.", self.num_rows, self.num_cols);
+        for cells in self.grid.chunks(6) {
+            let bits = cells
+                .iter()
+                .enumerate()
+                .filter(|(_bit, cell)| *cell.kind() == CellKind::Mine)
+                .fold(0, |bits, (bit, _cell)| bits | 1 << bit);
+            code.push(CODE_ALPHABET[bits] as char);
+        }
+        code
+    }
+
+    /// builds a new grid from a code produced by `to_code`. Every cell starts out hidden
+    pub fn from_code(code: &str) -> Result<Self, CodeError> {
+        let (dimensions, mines) = code.trim().split_once('.').ok_or(CodeError::Malformed)?;
+        let (rows, cols) = dimensions.split_once('x').ok_or(CodeError::Malformed)?;
+        let parse_dimension = |n: &str| {
+            n.parse::<usize>()
+                .ok()
+                .filter(|n| *n > 0)
+                .ok_or(CodeError::Malformed)
+        };
+        let (num_rows, num_cols) = (parse_dimension(rows)?, parse_dimension(cols)?);
+        let num_cells = num_rows.checked_mul(num_cols).ok_or(CodeError::Malformed)?;
+
+        let expected = num_cells.div_ceil(6);
+        let found = mines.chars().count();
+        if found != expected {
+            return Err(CodeError::WrongLength { expected, found });
+        }
+        let mut mine_ndxs = vec![];
+        for (chunk, ch) in mines.chars().enumerate() {
+            let bits = CODE_ALPHABET
+                .iter()
+                .position(|b| *b as char == ch)
+                .ok_or(CodeError::UnknownChar(ch))?;
+            mine_ndxs.extend(
+                (0..6)
+                    .filter(|bit| bits & 1 << bit != 0)
+                    .map(|bit| chunk * 6 + bit)
+                    .filter(|ndx| *ndx < num_cells),
+            );
+        }
+        Ok(Grid::with_mines(num_rows, num_cols, &mine_ndxs))
+    }
+
+    /// returns `true` if `other` has the same mine layout as this grid, or as one of its
+    /// rotations and reflections. A grid that is rotated a quarter turn may match an `other` with
+    /// its rows and columns swapped
+    pub fn is_isomorphic(&self, other: &Self) -> bool {
+        let mines = other.mine_indices();
+        let matches =
+            |grid: &Self| grid.dimensions() == other.dimensions() && grid.mine_indices() == mines;
+
+        // the quarter turned grid, mirrored, covers the two reflections across the diagonals
+        let quarter_turn = self.transform(Transform::Rotate90);
+        let mut variants = vec![
+            quarter_turn.transform(Transform::FlipH),
+            quarter_turn.transform(Transform::FlipV),
+        ];
+        variants.extend(
+            [
+                Transform::Rotate180,
+                Transform::Rotate270,
+                Transform::FlipH,
+                Transform::FlipV,
+            ]
+            .iter()
+            .map(|t| self.transform(*t)),
+        );
+        matches(self) || matches(&quarter_turn) || variants.iter().any(matches)
+    }
+
+    /// returns the background color of the cell at `index` and the color of the marker drawn in
+    /// its center, if it has one
+    fn rgba_colors(&self, index: usize) -> ([u8; 4], Option<[u8; 4]>) {
+        let cell = &self.grid[index];
+        match cell.state() {
+            CellState::Revealed => match cell.kind() {
+                CellKind::Mine => (REVEALED_RGBA, Some(MINE_RGBA)),
+                CellKind::Empty if cell.adj_mine_count() > 0 => (
+                    REVEALED_RGBA,
+                    Some(NUMBER_RGBA[cell.adj_mine_count() as usize - 1]),
+                ),
+                CellKind::Empty => (REVEALED_RGBA, None),
+            },
+            CellState::Marked(CellMarker::Flagged) => (HIDDEN_RGBA, Some(FLAG_RGBA)),
+            CellState::Marked(CellMarker::Questioned) => (HIDDEN_RGBA, Some(QUESTION_RGBA)),
+            CellState::Hidden => (HIDDEN_RGBA, None),
+        }
+    }
+
+    /// renders the grid, as the player sees it, into an RGBA pixel buffer where every cell is a
+    /// `cell_px` by `cell_px` square. Hidden cells are gray and revealed cells light gray, while
+    /// mines, flags, question marks and numbers are drawn as a colored square in the middle of
+    /// their cell. Returns the width and height of the image in pixels along with the buffer, in
+    /// row major order with four bytes per pixel
+    pub fn to_rgba(&self, cell_px: usize) -> (usize, usize, Vec<u8>) {
+        let width = self.num_cols * cell_px;
+        let height = self.num_rows * cell_px;
+        let mut pixels = Vec::with_capacity(width * height * 4);
+        // the marker covers the middle half of a cell
+        let marker_px = (cell_px / 4)..(cell_px - cell_px / 4);
+        for y in 0..height {
+            for x in 0..width {
+                let (background, marker) = self.rgba_colors(self.to_1d(y / cell_px, x / cell_px));
+                let color = match marker {
+                    Some(color)
+                        if marker_px.contains(&(x % cell_px))
+                            && marker_px.contains(&(y % cell_px)) =>
+                    {
+                        color
+                    }
+                    _ => background,
+                };
+                pixels.extend_from_slice(&color);
+            }
+        }
+        (width, height, pixels)
+    }
+
+    /// renders row `r` of the grid the same way the grid's Display does, without a line break
+    fn render_row(&self, r: usize) -> String {
+        (0..self.num_cols)
+            .map(|c| format!(" {}", self.glyph(r, c)))
+            .collect()
+    }
+
+    /// returns the index and rendering of every row that changed since the last call, so that
+    /// only those rows need to be redrawn. The first call returns every row
+    pub fn render_dirty_rows(&mut self) -> Vec<(usize, String)> {
+        let rows = (0..self.num_rows)
+            .map(|r| self.render_row(r))
+            .collect::<Vec<String>>();
+        let dirty = rows
+            .iter()
+            .enumerate()
+            .filter(|(r, row)| self.rendered_rows.get(*r) != Some(*row))
+            .map(|(r, row)| (r, row.clone()))
+            .collect();
+        self.rendered_rows = rows;
+        dirty
+    }
+
+    /// returns a new grid that is this grid rotated or mirrored by `t`. The mines and cell states
+    /// move along with their cells and the adjacent mine counts are recomputed. The game settings
+    /// are kept, while the reveal history, notes and statistics start over
+    pub fn transform(&self, t: Transform) -> Self {
+        let (rows, cols) = (self.num_rows, self.num_cols);
+        let (num_rows, num_cols) = match t {
+            Transform::Rotate90 | Transform::Rotate270 => (cols, rows),
+            _ => (rows, cols),
+        };
+        // the index, in this grid, of the cell that moves to r,c of the transformed grid
+        let source_ndx = |r: usize, c: usize| {
+            let (sr, sc) = match t {
+                Transform::Rotate90 => (rows - 1 - c, r),
+                Transform::Rotate180 => (rows - 1 - r, cols - 1 - c),
+                Transform::Rotate270 => (c, cols - 1 - r),
+                Transform::FlipH => (r, cols - 1 - c),
+                Transform::FlipV => (rows - 1 - r, c),
+            };
+            self.to_1d(sr, sc)
+        };
+        let sources = (0..num_rows * num_cols)
+            .map(|ndx| source_ndx(ndx / num_cols, ndx % num_cols))
+            .collect::<Vec<usize>>();
+        let mine_ndxs = sources
+            .iter()
+            .enumerate()
+            .filter(|(_ndx, src)| *self.grid[**src].kind() == CellKind::Mine)
+            .map(|(ndx, _src)| ndx)
+            .collect::<Vec<usize>>();
+
+        let mut grid = Grid::with_mines(num_rows, num_cols, &mine_ndxs);
+        for (cell, src) in grid.grid.iter_mut().zip(sources) {
+            cell.set_state(*self.grid[src].state());
+        }
+        grid.training_mode = self.training_mode;
+        grid.solution_limit = self.solution_limit;
+        grid.theme = self.theme.clone();
+        grid.questions_enabled = self.questions_enabled;
+        grid.flags_enabled = self.flags_enabled;
+        grid.auto_chord = self.auto_chord;
+        grid.win_condition = self.win_condition;
+        grid.protect_questioned = self.protect_questioned;
+        grid
+    }
+
+    /// returns a hash of the grid's layout, its dimensions and mine positions. The state of
+    /// play (revealed cells, markers...) is ignored, so identical layouts hash equally. The hash
+    /// is FNV-1a over the rows, the columns and the index of each mine, in row major order, each
+    /// as 8 little endian bytes. It is spelled out here so that it never changes between builds
+    pub fn layout_hash(&self) -> u64 {
+        let mine_ndxs =
+            (0..self.grid.len()).filter(|ndx| *self.grid[*ndx].kind() == CellKind::Mine);
+        [self.num_rows, self.num_cols]
+            .into_iter()
+            .chain(mine_ndxs)
+            .flat_map(|value| (value as u64).to_le_bytes())
+            .fold(FNV_OFFSET_BASIS, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+            })
+    }
+
+    /// returns how many empty cells there are for each adjacent mine count, 0 through 8
+    pub fn count_distribution(&self) -> [usize; 9] {
+        let mut distribution = [0; 9];
+        for cell in self.grid.iter() {
+            if *cell.kind() == CellKind::Empty {
+                distribution[cell.adj_mine_count() as usize] += 1;
+            }
+        }
+        distribution
+    }
+
+    /// returns the row,col indices of the cells that clicking `r`,`c` would reveal on a fresh
+    /// grid (the opening and its border), in row major order. Nothing is revealed if the cell
+    /// holds a mine. The grid itself is left untouched
+    pub fn reachable_safe_from(&self, r: usize, c: usize) -> Vec<(usize, usize)> {
+        let index = self.to_1d(r, c);
+        if *self.grid[index].kind() == CellKind::Mine {
+            return vec![];
+        }
+        let mut fresh = self.clone();
+        for cell in fresh.grid.iter_mut() {
+            cell.set_state(CellState::Hidden);
+        }
+        let mut revealed = vec![];
+        fresh.reveal_index(index, &mut revealed);
+
+        let mut reachable = revealed
+            .into_iter()
+            .map(|(ndx, _state)| (ndx / self.num_cols, ndx % self.num_cols))
+            .collect::<Vec<(usize, usize)>>();
+        reachable.sort();
+        reachable
+    }
+
+    /// returns the cell whose click would reveal the most cells on a fresh grid, see
+    /// `reachable_safe_from`. Ties go to the first cell in row major order. Returns `None` if
+    /// every cell holds a mine
+    pub fn best_opening(&self) -> Option<(usize, usize)> {
+        let mut best: Option<((usize, usize), usize)> = None;
+        for index in 0..self.grid.len() {
+            let pos = (index / self.num_cols, index % self.num_cols);
+            if *self.grid[index].kind() == CellKind::Mine {
+                continue;
+            }
+            let size = self.reachable_safe_from(pos.0, pos.1).len();
+            if best.is_none_or(|(_best, best_size)| size > best_size) {
+                best = Some((pos, size));
+            }
+        }
+        best.map(|(pos, _size)| pos)
+    }
+
+    /// scores how fair the mine layout is, from 0.0 for a board that comes down to a coin flip
+    /// to 1.0 for a board that can be solved by deduction alone. Play is simulated on a fresh copy
+    /// of the grid, opening with `best_opening` and then following the `suggested_reveal_order`.
+    /// If that reveals every empty cell the score is 1.0. If it stalls with one of the
+    /// `fifty_fifties` left, the score is 0.0. Otherwise a guess is needed, and the score is the
+    /// share of empty cells revealed before it
+    pub fn fairness(&self) -> f64 {
+        let (r, c) = match self.best_opening() {
+            Some(opening) => opening,
+            None => return 1.0,
+        };
+        let mine_ndxs = self
+            .mine_indices()
+            .iter()
+            .map(|(mr, mc)| self.to_1d(*mr, *mc))
+            .collect::<Vec<usize>>();
+        let mut board = Grid::with_mines(self.num_rows, self.num_cols, &mine_ndxs);
+        board.reveal_cell(r, c);
+        for (sr, sc) in board.suggested_reveal_order() {
+            board.reveal_cell(sr, sc);
+        }
+
+        let remaining = board.remaining_safe_cells();
+        if remaining == 0 {
+            return 1.0;
+        }
+        if !board.fifty_fifties().is_empty() {
+            return 0.0;
+        }
+        let safe_cells = self.grid.len() - self.num_mines;
+        (safe_cells - remaining) as f64 / safe_cells as f64
+    }
+
+    /// returns the number of distinct mines outside of `region` that are adjacent to at least one
+    /// of its cells
+    pub fn region_border_mines(&self, region: &[(usize, usize)]) -> usize {
+        region
+            .iter()
+            .flat_map(|(r, c)| self.neighbors(*r, *c))
+            .filter(|pos| {
+                !region.contains(pos) && *self.cell(pos.0, pos.1).kind() == CellKind::Mine
+            })
+            .collect::<HashSet<(usize, usize)>>()
+            .len()
+    }
+
+    /// returns the row,col indices of every cell currently marked with `marker`, in row major
+    /// order
+    pub fn cells_with_marker(&self, marker: CellMarker) -> Vec<(usize, usize)> {
+        (0..self.grid.len())
+            .filter(|ndx| self.grid[*ndx].marker() == Some(marker))
+            .map(|ndx| (ndx / self.num_cols, ndx % self.num_cols))
+            .collect()
+    }
+
+    /// returns the classic "mines remaining" counter: the number of mines minus the number of
+    /// flags placed. It goes negative when there are more flags than mines
+    pub fn mines_remaining(&self) -> i64 {
+        self.total_mines() as i64 - self.cells_with_marker(CellMarker::Flagged).len() as i64
+    }
+
+    /// removes every flag and question mark, leaving the revealed cells as they are
+    pub fn clear_markers(&mut self) {
+        for cell in self.grid.iter_mut() {
+            if let CellState::Marked(_) = cell.state() {
+                cell.set_state(CellState::Hidden);
+            }
+        }
+    }
+
+    /// returns the row,col indices of the mines that are not flagged, including a mine that was
+    /// revealed. Useful for reviewing a lost game
+    pub fn unflagged_mines(&self) -> Vec<(usize, usize)> {
+        self.mine_indices()
+            .into_iter()
+            .filter(|(r, c)| !self.cell(*r, *c).is_flagged())
+            .collect()
+    }
+
+    /// returns the row,col indices of the mines that still have to be flagged to win the game.
+    /// Under `RevealAllSafe`, flags are optional and none are required
+    pub fn required_flags(&self) -> Vec<(usize, usize)> {
+        match self.win_condition {
+            WinCondition::FlagAllMines => self.unflagged_mines(),
+            WinCondition::RevealAllSafe => vec![],
+        }
+    }
+
+    /// returns `true` if flagging the cell at `r`,`c` would win the game, because it holds the
+    /// last mine still to be flagged. This is never the case under `RevealAllSafe`, where flags
+    /// are optional
+    pub fn would_win_on_flag(&self, r: usize, c: usize) -> bool {
+        matches!(
+            self.cell(r, c).state(),
+            CellState::Hidden | CellState::Marked(CellMarker::Questioned)
+        ) && self.required_flags() == [(r, c)]
+    }
+
+    /// returns the number of flags placed on cells that hold a mine
+    pub fn correct_flags(&self) -> usize {
+        self.grid
+            .iter()
+            .filter(|cell| cell.is_flagged() && *cell.kind() == CellKind::Mine)
+            .count()
+    }
+
+    /// returns the number of flags placed on empty cells
+    pub fn incorrect_flags(&self) -> usize {
+        self.grid
+            .iter()
+            .filter(|cell| cell.is_flagged() && *cell.kind() == CellKind::Empty)
+            .count()
+    }
+
+    /// returns the row,col index of the first mine in row major order, or `None` if the grid
+    /// has no mines
+    pub fn first_mine(&self) -> Option<(usize, usize)> {
+        self.grid
+            .iter()
+            .position(|cell| *cell.kind() == CellKind::Mine)
+            .map(|ndx| (ndx / self.num_cols, ndx % self.num_cols))
+    }
+
+    /// returns the fraction of cells on the grid that actually hold a mine
+    pub fn actual_density(&self) -> f64 {
+        self.num_mines as f64 / (self.num_rows * self.num_cols) as f64
+    }
+
+    /// returns, for every cell, the Chebyshev distance to the nearest mine, that is the number of
+    /// king moves it takes to reach one. Mines are at distance 0. On a grid without mines every
+    /// distance is `usize::MAX`
+    pub fn mine_distance_field(&self) -> Vec<Vec<usize>> {
+        let mut distances = vec![usize::MAX; self.grid.len()];
+        // a breadth first search that starts from every mine at once
+        let mut queue = VecDeque::new();
+        for (index, cell) in self.grid.iter().enumerate() {
+            if *cell.kind() == CellKind::Mine {
+                distances[index] = 0;
+                queue.push_back(index);
+            }
+        }
+        while let Some(index) = queue.pop_front() {
+            for adj_ndx in self.adjacent(index).iter().copied() {
+                if distances[adj_ndx] == usize::MAX {
+                    distances[adj_ndx] = distances[index] + 1;
+                    queue.push_back(adj_ndx);
+                }
+            }
+        }
+        distances
+            .chunks(self.num_cols)
+            .map(|row| row.to_vec())
+            .collect()
+    }
+
+    /// returns the fraction of the cells without a mine that are lone cells. A high openness
+    /// means large cascades and an easier board. A grid without empty cells has an openness of 0
+    pub fn openness(&self) -> f64 {
+        let empty = self.grid.len() - self.num_mines;
+        if empty == 0 {
+            return 0.0;
+        }
+        let lone = self.grid.iter().filter(|cell| cell.is_lone_cell()).count();
+        lone as f64 / empty as f64
+    }
+
+    /// returns the distinct adjacent mine counts of the revealed numbers, in ascending order.
+    /// Revealed lone cells show no number and are left out
+    pub fn distinct_visible_numbers(&self) -> Vec<u8> {
+        let mut numbers = self
+            .grid
+            .iter()
+            .filter(|cell| *cell.state() == CellState::Revealed && *cell.kind() == CellKind::Empty)
+            .map(|cell| cell.adj_mine_count())
+            .filter(|count| *count > 0)
+            .collect::<Vec<u8>>();
+        numbers.sort_unstable();
+        numbers.dedup();
+        numbers
+    }
+
+    /// returns the number of free moves (random safe reveals) used this game
+    pub fn free_moves(&self) -> usize {
+        self.free_moves
+    }
+
+    /// attaches a note to the cell at `r`,`c`, replacing any previous note
+    pub fn set_note(&mut self, r: usize, c: usize, note: &str) {
+        self.notes.insert((r, c), note.to_string());
+    }
+
+    /// returns the note attached to the cell at `r`,`c`, if any
+    pub fn get_note(&self, r: usize, c: usize) -> Option<&str> {
+        self.notes.get(&(r, c)).map(|note| note.as_str())
+    }
+
+    /// reveals every opening (connected region of lone cells) on the grid along with the
+    /// numbered cells bordering them. Mines are never revealed by this
+    pub fn open_all_zeros(&mut self) {
+        let mut revealed = vec![];
+        for index in 0..self.grid.len() {
+            if self.grid[index].is_lone_cell() {
+                self.reveal_index(index, &mut revealed);
+            }
+        }
+        self.log_reveal(revealed);
+    }
+
+    /// debug helper that reveals every empty cell with exactly `count` adjacent mines, without
+    /// cascading into neighboring cells
+    pub fn reveal_by_count(&mut self, count: u8) {
+        for cell in self.grid.iter_mut() {
+            if *cell.kind() == CellKind::Empty && cell.adj_mine_count() == count {
+                cell.set_state(CellState::Revealed);
+            }
+        }
+    }
+
+    /// returns the "3BV" (Bechtel's Board Benchmark Value) of the grid. This is the minimum number
+    /// of clicks needed to reveal every empty cell: one click per opening (a connected region of
+    /// lone cells) plus one click for every numbered cell that does not border an opening
+    pub fn board_3bv(&self) -> usize {
+        let mut opened = vec![false; self.grid.len()];
+        let mut clicks = 0;
+        for index in 0..self.grid.len() {
+            if opened[index] || !self.grid[index].is_lone_cell() {
+                continue;
+            }
+            clicks += 1;
+            for ndx in self.connected_lone_cell_indices(index) {
+                opened[ndx] = true;
+                for adj_ndx in self.adjacent(ndx) {
+                    opened[*adj_ndx] = true;
+                }
+            }
+        }
+        clicks
+            + self
+                .grid
+                .iter()
+                .enumerate()
+                .filter(|(ndx, cell)| *cell.kind() == CellKind::Empty && !opened[*ndx])
+                .count()
+    }
+
+    /// returns a short description of the grid's difficulty, such as
+    /// "Medium (3BV 42, 16% density)". The label combines three ratings, each scoring 0 to 2:
+    /// * the mine density, following the classic levels: beginner boards hold about 12% mines,
+    ///   intermediate about 16% and expert about 21%
+    /// * the 3BV per cell, how many clicks each cell of the board costs
+    /// * the solver's `fairness`, boards that need guessing are harder
+    ///
+    /// A total of up to 1 is "Easy", up to 3 is "Medium", and above that "Hard"
+    pub fn difficulty_label(&self) -> String {
+        let density = self.actual_density() * 100.0;
+        let board_3bv = self.board_3bv();
+        let density_score = if density < 14.0 {
+            0
+        } else if density < 18.0 {
+            1
+        } else {
+            2
+        };
+        let clicks_per_cell = board_3bv as f64 / self.grid.len() as f64;
+        let clicks_score = if clicks_per_cell < 0.25 {
+            0
+        } else if clicks_per_cell < 0.4 {
+            1
+        } else {
+            2
+        };
+        let fairness = self.fairness();
+        let guessing_score = if fairness >= 1.0 {
+            0
+        } else if fairness >= 0.5 {
+            1
+        } else {
+            2
+        };
+        let label = match density_score + clicks_score + guessing_score {
+            0..=1 => "Easy",
+            2..=3 => "Medium",
+            _ => "Hard",
+        };
+        format!("{} (3BV {}, {:.0}% density)", label, board_3bv, density)
+    }
+
+    /// estimates the clicks still needed to reveal every empty cell, like `board_3bv` does for
+    /// the whole game: one click per opening that still has a hidden lone cell, plus one click
+    /// for every other hidden empty cell
+    pub fn estimated_remaining_clicks(&self) -> usize {
+        let mut covered = vec![false; self.grid.len()];
+        let mut clicks = 0;
+        for index in 0..self.grid.len() {
+            if covered[index] || !self.grid[index].is_lone_cell() {
+                continue;
+            }
+            let opening = self.connected_lone_cell_indices(index);
+            let is_open = opening
+                .iter()
+                .all(|ndx| *self.grid[*ndx].state() == CellState::Revealed);
+            for ndx in opening {
+                covered[ndx] = true;
+                // the border of an opening that is yet to be clicked is revealed along with it
+                if !is_open {
+                    for adj_ndx in self.adjacent(ndx) {
+                        covered[*adj_ndx] = true;
+                    }
+                }
+            }
+            if !is_open {
+                clicks += 1;
+            }
+        }
+        clicks
+            + self
+                .grid
+                .iter()
+                .enumerate()
+                .filter(|(ndx, cell)| {
+                    *cell.kind() == CellKind::Empty
+                        && *cell.state() != CellState::Revealed
+                        && !covered[*ndx]
+                })
+                .count()
+    }
+
+    /// reveals the cell at `r`,`c` like `reveal_cell`, but opens at most `max_cells` cells of the
+    /// resulting cascade, a `max_cells` of zero is treated as one. Returns the row,col indices of
+    /// the cells opened by this step and whether any cells are still pending. While cells are
+    /// pending, each call continues the pending cascade and `r`,`c` are ignored. Pending cells
+    /// that were flagged (or protected by a question) in the meantime are skipped. Each step can
+    /// be undone on its own
+    pub fn reveal_cell_stepped(
+        &mut self,
+        r: usize,
+        c: usize,
+        max_cells: usize,
+    ) -> (Vec<(usize, usize)>, bool) {
+        if self.pending_reveals.is_empty() {
+            let index = self.to_1d(r, c);
+            if self.is_protected_question(index) {
+                return (vec![], false);
+            }
+            // play the whole reveal to find the cells it would open, then hide them again
+            let detonating_chord = self.detonating_chord.take();
+            let mut revealed = vec![];
+            self.reveal_index(index, &mut revealed);
+            if self.auto_chord {
+                self.auto_chord_revealed(&mut revealed);
+            }
+            for (ndx, state) in revealed.iter().rev() {
+                self.grid[*ndx].set_state(*state);
+            }
+            self.detonating_chord = detonating_chord;
+            if !revealed.is_empty() {
+                self.clicks += 1;
+            }
+            self.pending_reveals = revealed.into_iter().map(|(ndx, _state)| ndx).collect();
+        }
+
+        let budget = max_cells.max(1);
+        let mut revealed = vec![];
+        let mut taken = 0;
+        while revealed.len() < budget && taken < self.pending_reveals.len() {
+            let ndx = self.pending_reveals[taken];
+            taken += 1;
+            let state = *self.grid[ndx].state();
+            if state != CellState::Revealed
+                && !self.grid[ndx].is_flagged()
+                && !self.is_protected_question(ndx)
+            {
+                revealed.push((ndx, state));
+                self.grid[ndx].set_state(CellState::Revealed);
+            }
+        }
+        self.pending_reveals.drain(..taken);
+        let opened = revealed
+            .iter()
+            .map(|(ndx, _state)| (ndx / self.num_cols, ndx % self.num_cols))
+            .collect();
+        self.log_reveal(revealed);
+        (opened, !self.pending_reveals.is_empty())
+    }
+
+    /// returns the number of reveal and chord clicks, that opened at least one cell,
+    /// made so far
+    pub fn clicks(&self) -> usize {
+        self.clicks
+    }
+
+    /// returns the clicks saved by chording: the number of cells opened by chords minus the
+    /// number of chords that opened them
+    pub fn chord_efficiency(&self) -> usize {
+        self.chord_reveals - self.chord_clicks
+    }
+
+    /// returns the click efficiency of the game, the 3BV of the grid divided by the number of
+    /// clicks made, capped at `1.0`. Part way through a game, or with chords and free reveals,
+    /// fewer clicks than the 3BV can be made, which is still counted as a perfect `1.0`. Returns
+    /// `0.0` if no clicks have been made
+    pub fn efficiency(&self) -> f64 {
+        if self.clicks == 0 {
+            0.0
+        } else {
+            (self.board_3bv() as f64 / self.clicks as f64).min(1.0)
+        }
+    }
+
+    /// returns `true` once the first reveal of the game has been made
+    pub fn has_started(&self) -> bool {
+        self.started
+    }
+
+    /// undoes the most recent reveal, including any cells opened by its cascade. Cells are
+    /// restored to the state they had before the reveal, so markers placed before it are kept.
+    /// Returns `false` if there was no reveal to undo
+    pub fn undo_reveal(&mut self) -> bool {
+        match self.reveal_log.pop() {
+            Some(revealed) => {
+                let history_len = self.reveal_history.len() - revealed.len();
+                self.reveal_history.truncate(history_len);
+                for (index, state) in revealed {
+                    self.grid[index].set_state(state);
+                }
+                if !self.is_game_lost() {
+                    self.detonating_chord = None;
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// returns a copy of the grid for spectators, holding only what the player can see. Every
+    /// cell that is not revealed is turned into an empty cell with no adjacent mines, so that
+    /// sending the copy to a spectator can't leak where the mines are. Flags and question marks
+    /// are kept, while x-ray, the seed the mines were placed from, the cells still pending from a
+    /// stepped reveal, the detonating chord, the notes and the last rendered rows are dropped. The
+    /// copy draws the same as the grid, but it can't be played
+    pub fn spectator_view(&self) -> Self {
+        let mut view = self.clone();
+        for cell in view.grid.iter_mut() {
+            if *cell.state() != CellState::Revealed {
+                cell.set_kind(CellKind::Empty);
+                cell.set_adj_mine_count(0);
+            }
+        }
+        view.xray = false;
+        view.seed = None;
+        view.pending_reveals.clear();
+        view.detonating_chord = None;
+        view.notes.clear();
+        view.rendered_rows.clear();
+        view
+    }
+
+    /// returns the row,col index of every cell revealed so far, in the order they were revealed.
+    /// Undoing a reveal removes its cells from the history. Cells that were revealed when the
+    /// grid was built, e.g. by `from_state_layout`, are not part of it
+    pub fn reveal_history(&self) -> &[(usize, usize)] {
+        &self.reveal_history
+    }
+
+    /// returns a reference to the cell at row `r` and column `c`
+    pub fn cell(&self, r: usize, c: usize) -> &Cell {
+        &self.grid[self.to_1d(r, c)]
+    }
+
+    /// returns the 1D index of the cell at `r`,`c`, or an `OutOfBounds` error if it is not on
+    /// the grid
+    fn checked_index(&self, r: usize, c: usize) -> Result<usize, MinesweeperError> {
+        if r < self.num_rows && c < self.num_cols {
+            Ok(self.to_1d(r, c))
+        } else {
+            Err(MinesweeperError::OutOfBounds {
+                r,
+                c,
+                rows: self.num_rows,
+                cols: self.num_cols,
+            })
+        }
+    }
+
+    /// returns a reference to the cell at row `r` and column `c`, or an error if it is not on
+    /// the grid
+    pub fn try_cell(&self, r: usize, c: usize) -> Result<&Cell, MinesweeperError> {
+        self.checked_index(r, c).map(|index| &self.grid[index])
+    }
+
+    /// reveals the cell at `r`,`c` like `reveal_cell`, or returns an error if it is not on the grid
+    pub fn try_reveal_cell(&mut self, r: usize, c: usize) -> Result<(), MinesweeperError> {
+        self.checked_index(r, c)?;
+        self.reveal_cell(r, c);
+        Ok(())
+    }
+
+    /// toggles `mark` on the cell at `r`,`c` like `toggle_mark`, or returns an error if it is not
+    /// on the grid
+    pub fn try_toggle_mark(
+        &mut self,
+        r: usize,
+        c: usize,
+        mark: CellMarker,
+    ) -> Result<(), MinesweeperError> {
+        self.checked_index(r, c)?;
+        self.toggle_mark(r, c, mark);
+        Ok(())
+    }
+
+    /// chords the cell at `r`,`c` like `chord_cell`, or returns an error if it is not on the grid
+    pub fn try_chord_cell(&mut self, r: usize, c: usize) -> Result<(), MinesweeperError> {
+        self.checked_index(r, c)?;
+        self.chord_cell(r, c);
+        Ok(())
+    }
+
+    /// returns the row,col indices of all cells adjacent to the cell at `r`,`c`, or an error if
+    /// it is not on the grid
+    pub fn try_neighbors(
+        &self,
+        r: usize,
+        c: usize,
+    ) -> Result<Vec<(usize, usize)>, MinesweeperError> {
+        self.checked_index(r, c)?;
+        Ok(self.neighbors(r, c))
+    }
+
+    /// reveals the cell at `r`,`c` like `reveal_cell` and returns the row,col indices of the cells
+    /// it opened. If the cell holds a mine, it is left hidden and a `HitMine` error is returned
+    /// instead, so the caller can decide whether to go through with the reveal. Only the cell
+    /// itself is checked, a reveal that is auto chorded can still detonate misplaced flags
+    pub fn try_reveal_safe(
+        &mut self,
+        r: usize,
+        c: usize,
+    ) -> Result<Vec<(usize, usize)>, MinesweeperError> {
+        let index = self.checked_index(r, c)?;
+        let cell = &self.grid[index];
+        if *cell.kind() == CellKind::Mine
+            && *cell.state() != CellState::Revealed
+            && !self.is_protected_question(index)
+        {
+            return Err(MinesweeperError::HitMine { r, c });
+        }
+        let logged = self.reveal_log.len();
+        self.reveal_cell(r, c);
+        if self.reveal_log.len() == logged {
+            return Ok(vec![]);
+        }
+        Ok(self.reveal_log[logged]
+            .iter()
+            .map(|(ndx, _state)| (ndx / self.num_cols, ndx % self.num_cols))
+            .collect())
+    }
+
+    /// returns a mutable reference to the cell at row `r` and column `c`
+    #[cfg(test)]
+    pub(crate) fn cell_mut(&mut self, r: usize, c: usize) -> &mut Cell {
+        let index = self.to_1d(r, c);
+        &mut self.grid[index]
+    }
+
+    /// returns `true` if the cell at `r`,`c` is a revealed number with as many adjacent flags
+    /// as adjacent mines, so chording it would reveal its other neighbors
+    pub fn can_chord(&self, r: usize, c: usize) -> bool {
+        self.is_chordable(self.to_1d(r, c))
+    }
+
+    /// returns `true` if the cell at `r`,`c` is a revealed number whose adjacent mine count is
+    /// met exactly by the flags around it
+    pub fn is_satisfied(&self, r: usize, c: usize) -> bool {
+        *self.cell(r, c).kind() == CellKind::Empty && self.can_chord(r, c)
+    }
+
+    /// returns the kind of the cell at `r`,`c` once it is revealed or the game is over, and
+    /// `None` while it could still spoil the game
+    pub fn revealed_kind(&self, r: usize, c: usize) -> Option<CellKind> {
+        let cell = self.cell(r, c);
+        if *cell.state() == CellState::Revealed || self.game_state() != GameState::InProgress {
+            Some(*cell.kind())
+        } else {
+            None
+        }
+    }
+
+    /// returns the row,col indices of all cells adjacent to the cell at `r`,`c`
+    pub fn neighbors(&self, r: usize, c: usize) -> Vec<(usize, usize)> {
+        self.adjacent(self.to_1d(r, c))
+            .iter()
+            .map(|ndx| (ndx / self.num_cols, ndx % self.num_cols))
+            .collect()
+    }
+
+    /// returns the maximum number of mine configurations the solver will enumerate
+    pub fn solution_limit(&self) -> usize {
+        self.solution_limit
+    }
+
+    /// sets the maximum number of mine configurations the solver will enumerate before giving up
+    pub fn set_solution_limit(&mut self, limit: usize) {
+        self.solution_limit = limit;
+    }
+
+    /// enables or disables training mode. In training mode, chording a cell that has misplaced
+    /// flags around it will remove those flags instead of revealing (and possibly detonating)
+    /// the neighboring cells
+    pub fn set_training_mode(&mut self, training_mode: bool) {
+        self.training_mode = training_mode;
+    }
+
+    /// returns `true` if training mode is enabled
+    pub fn training_mode(&self) -> bool {
+        self.training_mode
+    }
+
+    /// explains how a lost game was lost. If a chord revealed the mine, the explanation names
+    /// the chorded cell, the mine it revealed, and the misplaced flags that made the chord
+    /// reveal it
+    pub fn explain_loss(&self) -> String {
+        if !self.is_game_lost() {
+            return "the game has not been lost".to_string();
+        }
+        let describe = |cells: &[(usize, usize)]| {
+            cells
+                .iter()
+                .map(|(r, c)| format!("{} {}", r, c))
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+        let detonated = self
+            .mine_indices()
+            .into_iter()
+            .filter(|(r, c)| *self.cell(*r, *c).state() == CellState::Revealed)
+            .collect::<Vec<(usize, usize)>>();
+        if detonated.is_empty() {
+            return "the time limit ran out".to_string();
+        }
+
+        match &self.detonating_chord {
+            Some((index, misplaced)) => {
+                let (r, c) = (index / self.num_cols, index % self.num_cols);
+                let flags = if misplaced.len() == 1 {
+                    format!("flag at {} was", describe(misplaced))
+                } else {
+                    format!("flags at {} were", describe(misplaced))
+                };
+                format!(
+                    "the chord at {} {} revealed the mine at {}, because the {} misplaced",
+                    r,
+                    c,
+                    describe(&detonated),
+                    flags
+                )
+            }
+            None => format!("the mine at {} was revealed", describe(&detonated)),
+        }
+    }
+
+    /// returns the row,col indices of flagged cells, adjacent to the cell at `r`,`c`, that do
+    /// not contain a mine
+    pub fn misplaced_flags(&self, r: usize, c: usize) -> Vec<(usize, usize)> {
+        self.adjacent(self.to_1d(r, c))
+            .iter()
+            .copied()
+            .filter(|ndx| {
+                self.grid[*ndx].is_flagged() && *self.grid[*ndx].kind() == CellKind::Empty
+            })
+            .map(|ndx| (ndx / self.num_cols, ndx % self.num_cols))
+            .collect()
+    }
+
+    /// returns the number of flags placed around the cell at `r`,`c`. A revealed number can be
+    /// chorded once this equals its adjacent mine count
+    pub fn adjacent_flags(&self, r: usize, c: usize) -> usize {
+        self.adjacent_flag_count(self.to_1d(r, c))
+    }
+
+    /// returns the row,col indices of the hidden neighbors of the cell at `r`,`c`. Revealed and
+    /// flagged neighbors are excluded, questioned neighbors are still considered unrevealed
+    pub fn adjacent_unrevealed(&self, r: usize, c: usize) -> Vec<(usize, usize)> {
+        self.adjacent(self.to_1d(r, c))
+            .iter()
+            .copied()
+            .filter(|ndx| {
+                *self.grid[*ndx].state() != CellState::Revealed && !self.grid[*ndx].is_flagged()
+            })
+            .map(|ndx| (ndx / self.num_cols, ndx % self.num_cols))
+            .collect()
+    }
+}
+
+impl MineSweeperGame for Grid<Cell> {
+    fn init(num_rows: usize, num_cols: usize) -> Self {
+        Grid::init_with_seed(num_rows, num_cols, thread_rng().gen())
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        (self.num_rows, self.num_cols)
+    }
+
+    fn mine_indices(&self) -> Vec<(usize, usize)> {
+        self.grid
+            .iter()
+            .enumerate()
+            .filter(|(_ndx, cell)| *cell.kind() == CellKind::Mine)
+            .map(|(ndx, _cell)| (ndx / self.num_cols, ndx % self.num_cols))
+            .collect::<Vec<(usize, usize)>>()
+    }
+
+    /// returns the number of mines placed on the grid when it was built
+    fn total_mines(&self) -> usize {
+        self.num_mines
+    }
+
+    fn first_reveal(&mut self, r: usize, c: usize) -> RevealOutcome {
+        if !self.started {
+            self.clear_mines_around(self.to_1d(r, c));
+        }
+        self.reveal_cell(r, c)
+    }
+
+    fn reveal_cell(&mut self, r: usize, c: usize) -> RevealOutcome {
+        let index = self.to_1d(r, c);
+        if *self.grid[index].state() == CellState::Revealed {
+            return RevealOutcome::AlreadyRevealed;
+        }
+        if self.is_protected_question(index) {
+            return RevealOutcome::Protected;
+        }
+        let mut revealed = vec![];
+        self.reveal_index(index, &mut revealed);
+        if self.auto_chord {
+            self.auto_chord_revealed(&mut revealed);
+        }
+        let newly_revealed = revealed.len();
+        if self.log_reveal(revealed) {
+            self.clicks += 1;
+        }
+        match (self.grid[index].kind(), self.grid[index].adj_mine_count()) {
+            (CellKind::Mine, _count) => RevealOutcome::Mine,
+            (CellKind::Empty, 0) => RevealOutcome::Opening { newly_revealed },
+            (CellKind::Empty, count) => RevealOutcome::Number {
+                count,
+                newly_revealed,
+            },
+        }
+    }
+
+    fn reveal_all_lone_cells(&mut self, r: usize, c: usize) {
+        let mut revealed = vec![];
+        self.reveal_lone_cells_from(self.to_1d(r, c), &mut revealed);
+        self.log_reveal(revealed);
+    }
+
+    fn flag_cell(&mut self, r: usize, c: usize) {
+        let index = self.to_1d(r, c);
+        if self.flags_enabled && *self.grid[index].state() != CellState::Revealed {
+            self.grid[index].set_state(CellState::Marked(CellMarker::Flagged));
+        }
+    }
+
+    fn question_cell(&mut self, r: usize, c: usize) {
+        let index = self.to_1d(r, c);
+        if self.flags_enabled
+            && self.questions_enabled
+            && *self.grid[index].state() != CellState::Revealed
+        {
+            let questioned = CellState::Marked(CellMarker::Questioned);
+            if *self.grid[index].state() != questioned {
+                self.grid[index].set_state(questioned);
+                self.questions_used += 1;
+            }
+        }
+    }
+
+    fn unmark_cell(&mut self, r: usize, c: usize) {
+        let index = self.to_1d(r, c);
+        if *self.grid[index].state() != CellState::Revealed {
+            self.grid[index].set_state(CellState::Hidden);
+        }
+    }
+
+    fn toggle_mark(&mut self, r: usize, c: usize, mark: CellMarker) {
+        if !self.flags_enabled {
+            return;
+        }
+        let index = self.to_1d(r, c);
+        if let CellState::Marked(_) = self.grid[index].state() {
+            self.unmark_cell(r, c);
+        } else {
+            match mark {
+                CellMarker::Flagged => self.flag_cell(r, c),
+                CellMarker::Questioned => self.question_cell(r, c),
+            }
+        }
+    }
+
+    fn chord_cell(&mut self, r: usize, c: usize) {
+        let index = self.to_1d(r, c);
+        if !self.is_chordable(index) {
+            return;
+        }
+
+        // in training mode, wrong flags are removed rather than detonating a mine
+        if self.training_mode {
+            let misplaced = self.misplaced_flags(r, c);
+            if !misplaced.is_empty() {
+                for (mr, mc) in misplaced {
+                    self.unmark_cell(mr, mc);
+                }
+                return;
+            }
+        }
+
+        let mut revealed = vec![];
+        self.chord_index(index, &mut revealed);
+        let opened = revealed.len();
+        if self.log_reveal(revealed) {
+            self.clicks += 1;
+            self.chord_clicks += 1;
+            self.chord_reveals += opened;
+        }
+    }
+
+    fn is_game_won(&self) -> bool {
+        // there is nothing to flag on a grid without mines, it is won once a cell is revealed
+        if self.is_trivial() {
+            return self
+                .grid
+                .iter()
+                .any(|cell| *cell.state() == CellState::Revealed);
+        }
+        match self.win_condition {
+            WinCondition::FlagAllMines => self.mine_indices().iter().all(|(r, c)| {
+                let index = self.to_1d(*r, *c);
+                self.grid[index].is_flagged()
+            }),
+            WinCondition::RevealAllSafe => self.is_game_won_by_reveal(),
+        }
+    }
+
+    fn is_game_lost(&self) -> bool {
+        self.lost_on_time
+            || self.mine_indices().iter().any(|(r, c)| {
+                let index = self.to_1d(*r, *c);
+                *self.grid[index].state() == CellState::Revealed
+            })
+    }
+}
+
+impl fmt::Display for Grid<Cell> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut buf = String::new();
+        for ri in 0..self.num_rows {
+            buf.push_str(self.render_row(ri).as_str());
+            buf.push('\n')
+        }
+        write!(f, "{}", buf)
+    }
+}
+
+impl fmt::Debug for Grid<Cell> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut buf = String::new();
+        for ri in 0..self.num_rows {
+            for ci in 0..self.num_cols {
+                let index = self.to_1d(ri, ci);
+                buf.push_str(format!(" {:?}", self.grid[index]).as_str());
+            }
+            buf.push('\n')
+        }
+        write!(f, "{}", buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mine_sweeper_board::{
+        CellKind, CellMarker, CellState, GameState, MineDistribution, MineSweeperCell,
+        MineSweeperGame, RevealOutcome, Theme, Transform, WinCondition, FLAG, HIDDEN, MINE,
+        QUESTION, REVEALED, XRAY,
+    };
+    use crate::mine_sweeper_error::MinesweeperError;
+    use crate::mine_sweeper_impl::{
+        Cell, CodeError, Grid, LayoutError, FLAG_RGBA, HIDDEN_RGBA, NUMBER_RGBA, REVEALED_RGBA,
+    };
+    use rand::rngs::StdRng;
+    use rand::{thread_rng, Rng, SeedableRng};
+    use std::time::Duration;
+
+    #[test]
+    fn revealed_mined_cell_should_display_as_mine_char() {
+        let mined_cell = Cell {
+            state: CellState::Revealed,
+            kind: CellKind::Mine,
+            adj_mine_count: 0,
+        };
+        assert_eq!(format!("{}", mined_cell), MINE.to_string());
+    }
+
+    #[test]
+    fn hidden_cell_should_display_as_hidden_char() {
+        let mined_cell = Cell {
+            state: CellState::Hidden,
+            kind: CellKind::Mine,
+            adj_mine_count: 0,
         };
         assert_eq!(format!("{}", mined_cell), HIDDEN.to_string());
     }
 
     #[test]
-    fn revealed_empty_cell_with_no_adjacent_mines_should_display_revealed_char() {
-        let cell = Cell {
-            state: CellState::Revealed,
-            kind: CellKind::Empty,
-            adj_mine_count: 0,
-        };
-        assert_eq!(format!("{}", cell), REVEALED.to_string());
+    fn revealed_empty_cell_with_no_adjacent_mines_should_display_revealed_char() {
+        let cell = Cell {
+            state: CellState::Revealed,
+            kind: CellKind::Empty,
+            adj_mine_count: 0,
+        };
+        assert_eq!(format!("{}", cell), REVEALED.to_string());
+    }
+
+    #[test]
+    fn revealed_empty_cell_with_adjacent_mines_should_display_adj_mine_count() {
+        let cell = Cell {
+            state: CellState::Revealed,
+            kind: CellKind::Empty,
+            adj_mine_count: 3,
+        };
+        assert_eq!(format!("{}", cell), "3".to_string());
+    }
+
+    #[test]
+    fn flagged_cell_displays_the_flag_char() {
+        let cell = Cell {
+            state: CellState::Marked(CellMarker::Flagged),
+            kind: CellKind::Empty,
+            adj_mine_count: 1,
+        };
+        assert_eq!(format!("{}", cell), FLAG.to_string());
+    }
+
+    #[test]
+    fn questioned_cell_displays_the_question_char() {
+        let cell = Cell {
+            state: CellState::Marked(CellMarker::Questioned),
+            kind: CellKind::Empty,
+            adj_mine_count: 1,
+        };
+        assert_eq!(format!("{}", cell), QUESTION.to_string());
+    }
+
+    #[test]
+    fn lone_cell_test_should_return_true_for_empty_cell_with_0_adj_mines() {
+        let cell = Cell {
+            state: CellState::Marked(CellMarker::Flagged),
+            kind: CellKind::Empty,
+            adj_mine_count: 0,
+        };
+        assert!(cell.is_lone_cell());
+    }
+
+    #[test]
+    #[allow(clippy::bool_assert_comparison)]
+    fn lone_cell_test_should_return_false_for_empty_cell_with_gt_0_adj_mines() {
+        let cell = Cell {
+            state: CellState::Marked(CellMarker::Flagged),
+            kind: CellKind::Empty,
+            adj_mine_count: 2,
+        };
+        assert_eq!(cell.is_lone_cell(), false);
+    }
+
+    #[test]
+    #[allow(clippy::bool_assert_comparison)]
+    fn lone_cell_test_should_return_false_for_any_mined_cell() {
+        let cell = Cell {
+            state: CellState::Marked(CellMarker::Flagged),
+            kind: CellKind::Mine,
+            adj_mine_count: 2,
+        };
+        assert_eq!(cell.is_lone_cell(), false);
+    }
+
+    #[test]
+    fn adjacent_unrevealed_excludes_revealed_and_flagged_neighbors() {
+        // mine in the bottom right corner of a 3x3 grid
+        let mut grid = Grid::with_mines(3, 3, &[8]);
+        grid.grid[0].set_state(CellState::Revealed);
+        grid.grid[1].set_state(CellState::Revealed);
+        grid.flag_cell(2, 2);
+        grid.question_cell(1, 2);
+
+        let mut hidden = grid.adjacent_unrevealed(1, 1);
+        hidden.sort();
+        assert_eq!(hidden, vec![(0, 2), (1, 0), (1, 2), (2, 0), (2, 1)]);
+    }
+
+    #[test]
+    fn chord_reveals_the_unflagged_neighbors_of_a_satisfied_number() {
+        // mines at row 0, col 0 and row 0, col 2
+        let mut grid = Grid::with_mines(3, 3, &[0, 2]);
+        grid.grid[4].set_state(CellState::Revealed);
+        grid.flag_cell(0, 0);
+        assert_eq!(grid.adjacent_flags(1, 1), 1);
+
+        // one flag around a 2 is not enough to chord
+        grid.chord_cell(1, 1);
+        assert_eq!(grid.remaining_safe_cells(), 6);
+
+        grid.flag_cell(0, 2);
+        assert_eq!(grid.adjacent_flags(1, 1), 2);
+        grid.chord_cell(1, 1);
+        assert_eq!(grid.remaining_safe_cells(), 0);
+        assert!(!grid.is_game_lost());
+        assert!(grid.cell(0, 0).is_flagged() && grid.cell(0, 2).is_flagged());
+    }
+
+    #[test]
+    fn chord_with_misplaced_flag_in_training_mode_removes_flag_without_losing() {
+        // mine at row 0, col 0
+        let mut grid = Grid::with_mines(3, 3, &[0]);
+        grid.set_training_mode(true);
+        grid.grid[4].set_state(CellState::Revealed);
+        grid.flag_cell(2, 2);
+
+        grid.chord_cell(1, 1);
+
+        assert!(!grid.is_game_lost());
+        assert_eq!(*grid.grid[8].state(), CellState::Hidden);
+        assert_eq!(*grid.grid[0].state(), CellState::Hidden);
+    }
+
+    #[test]
+    fn chord_with_misplaced_flag_outside_training_mode_detonates() {
+        let mut grid = Grid::with_mines(3, 3, &[0]);
+        grid.grid[4].set_state(CellState::Revealed);
+        grid.flag_cell(2, 2);
+
+        grid.chord_cell(1, 1);
+
+        assert!(grid.is_game_lost());
+    }
+
+    #[test]
+    fn explain_loss_names_the_misplaced_flag_of_a_chord() {
+        let mut grid = Grid::with_mines(3, 3, &[0]);
+        assert_eq!(grid.explain_loss(), "the game has not been lost");
+        grid.grid[4].set_state(CellState::Revealed);
+        grid.flag_cell(2, 2);
+        grid.chord_cell(1, 1);
+        assert_eq!(
+            grid.explain_loss(),
+            "the chord at 1 1 revealed the mine at 0 0, because the flag at 2 2 was misplaced"
+        );
+
+        // undoing the chord forgets it, a plain reveal of the mine is explained as such
+        assert!(grid.undo_reveal());
+        grid.reveal_cell(0, 0);
+        assert_eq!(grid.explain_loss(), "the mine at 0 0 was revealed");
+    }
+
+    #[test]
+    fn undo_reveal_hides_the_last_cascade_and_keeps_earlier_flags() {
+        // mine at row 0, col 0 of a 4x4 grid
+        let mut grid = Grid::with_mines(4, 4, &[0]);
+        grid.flag_cell(0, 0);
+        grid.reveal_cell(3, 3);
+        assert_eq!(*grid.cell(1, 1).state(), CellState::Revealed);
+
+        assert!(grid.undo_reveal());
+
+        assert!(grid
+            .grid
+            .iter()
+            .all(|cell| *cell.state() != CellState::Revealed));
+        assert!(grid.cell(0, 0).is_flagged());
+        assert!(!grid.undo_reveal());
+    }
+
+    #[test]
+    fn spectator_view_hides_the_mines_but_draws_the_same() {
+        let mut grid = Grid::init_symmetric(6, 6, 8, 5);
+        let (r, c) = grid.best_opening().unwrap();
+        grid.reveal_cell(r, c);
+        let (mr, mc) = grid.first_mine().unwrap();
+        grid.flag_cell(mr, mc);
+        grid.set_xray(true);
+
+        let view = grid.spectator_view();
+        grid.set_xray(false);
+        assert_eq!(view.to_string(), grid.to_string());
+        assert!(view.mine_indices().is_empty());
+        assert_eq!(view.seed(), None);
+        for r in 0..6 {
+            for c in 0..6 {
+                let cell = view.cell(r, c);
+                if *cell.state() == CellState::Revealed {
+                    assert_eq!(cell.adj_mine_count(), grid.cell(r, c).adj_mine_count());
+                } else {
+                    assert_eq!(cell.adj_mine_count(), 0);
+                }
+            }
+        }
+        // no cell kind in the serialized view is a mine
+        assert!(!serde_json::to_string(&view).unwrap().contains("\"Mine\""));
+    }
+
+    #[test]
+    fn spectator_view_drops_pending_reveals_and_notes() {
+        let mut grid = Grid::with_mines(8, 8, &[63]);
+        grid.set_note(7, 6, "safe?");
+        let (_opened, pending) = grid.reveal_cell_stepped(0, 0, 10);
+        assert!(pending);
+
+        let view = grid.spectator_view();
+        assert!(view.pending_reveals.is_empty());
+        assert!(view.notes.is_empty());
+        assert!(view.detonating_chord.is_none());
+        let json = serde_json::to_string(&view).unwrap();
+        let restored: Grid<Cell> = serde_json::from_str(&json).unwrap();
+        assert!(restored.pending_reveals.is_empty());
+        assert!(restored.notes.is_empty());
+    }
+
+    #[test]
+    fn reveal_history_lists_every_revealed_cell_in_order() {
+        let mut grid = Grid::from_layout("*.*.\n....\n*...\n....").unwrap();
+        grid.reveal_cell(0, 1);
+        grid.reveal_cell(1, 0);
+        grid.reveal_cell(0, 3);
+        assert_eq!(grid.reveal_history(), &[(0, 1), (1, 0), (0, 3)]);
+
+        // the lone cell in the corner cascades into its neighbors, which come after it
+        grid.reveal_cell(3, 3);
+        assert_eq!(grid.reveal_history()[3], (3, 3));
+        let revealed = grid
+            .grid
+            .iter()
+            .filter(|cell| *cell.state() == CellState::Revealed)
+            .count();
+        assert_eq!(grid.reveal_history().len(), revealed);
+
+        assert!(grid.undo_reveal());
+        assert_eq!(grid.reveal_history(), &[(0, 1), (1, 0), (0, 3)]);
+    }
+
+    #[test]
+    fn the_first_reveal_is_never_a_mine() {
+        let mut rng = thread_rng();
+        for _ in 0..1000 {
+            let mut grid: Grid<Cell> = Grid::init(4, 4);
+            let mines = grid.total_mines();
+            let (r, c) = (rng.gen_range(0, 4), rng.gen_range(0, 4));
+            grid.first_reveal(r, c);
+            assert_eq!(*grid.cell(r, c).kind(), CellKind::Empty);
+            // the neighbors are kept clear as well, so the first reveal opens a cascade
+            assert_eq!(grid.cell(r, c).adj_mine_count(), 0);
+            assert!(!grid.is_game_lost());
+            assert_eq!(grid.total_mines(), mines);
+            assert_eq!(grid.mine_indices().len(), mines);
+        }
+    }
+
+    #[test]
+    fn the_first_reveal_only_clears_its_own_cell_on_a_crowded_grid() {
+        let mut grid = Grid::from_layout("***\n***\n**.").unwrap();
+        grid.first_reveal(1, 1);
+        assert_eq!(*grid.cell(1, 1).kind(), CellKind::Empty);
+        assert_eq!(grid.cell(1, 1).adj_mine_count(), 8);
+        assert_eq!(*grid.cell(2, 2).kind(), CellKind::Mine);
+        assert!(!grid.is_game_lost());
+
+        // once the game has started, the mines stay where they are
+        grid.first_reveal(0, 0);
+        assert!(grid.is_game_lost());
+    }
+
+    #[test]
+    fn game_has_started_after_the_first_reveal() {
+        let mut grid = Grid::with_mines(3, 3, &[0]);
+        assert!(!grid.has_started());
+
+        grid.flag_cell(0, 0);
+        assert!(!grid.has_started());
+
+        grid.reveal_cell(2, 2);
+        assert!(grid.has_started());
+    }
+
+    #[test]
+    fn board_3bv_counts_openings_and_isolated_numbers() {
+        // mines at row 0, col 2 and row 2, col 2 of a 3x5 grid. The left and right columns
+        // form two openings, the 2 in the center borders neither of them
+        let grid = Grid::with_mines(3, 5, &[2, 12]);
+        assert_eq!(grid.board_3bv(), 3);
+
+        // a single numbered cell that borders no opening adds a click
+        let grid = Grid::with_mines(1, 3, &[0, 2]);
+        assert_eq!(grid.board_3bv(), 1);
+    }
+
+    #[test]
+    fn efficiency_is_3bv_divided_by_clicks() {
+        let mut grid = Grid::with_mines(3, 5, &[2, 12]);
+        assert_eq!(grid.efficiency(), 0.0);
+
+        // one click so far, on a board with a 3BV of 3, is capped at a perfect efficiency
+        grid.reveal_cell(1, 0);
+        assert_eq!(grid.board_3bv(), 3);
+        assert_eq!(grid.efficiency(), 1.0);
+        grid.reveal_cell(1, 0);
+        grid.reveal_cell(1, 2);
+        grid.reveal_cell(1, 4);
+        assert_eq!(grid.clicks(), 3);
+        assert_eq!(grid.efficiency(), 1.0);
+
+        grid.undo_reveal();
+        grid.reveal_cell(1, 4);
+        assert_eq!(grid.efficiency(), 0.75);
+    }
+
+    /// the average number of mines adjacent to each mine, over several generated grids
+    fn avg_mine_adjacency(distribution: MineDistribution) -> f64 {
+        let mut total = 0;
+        let mut mines = 0;
+        // the grids are built from fixed seeds, so the comparison gives the same result every run
+        for seed in 0..10 {
+            let grid = match distribution {
+                MineDistribution::Uniform => Grid::init_with_seed(20, 20, seed),
+                MineDistribution::Clustered => {
+                    let mut rng = StdRng::seed_from_u64(seed);
+                    let mine_ndxs = Grid::gen_clustered_grid_indices(&mut rng, 20, 20, 60);
+                    Grid::with_mines(20, 20, &mine_ndxs)
+                }
+            };
+            for cell in grid
+                .grid
+                .iter()
+                .filter(|cell| *cell.kind() == CellKind::Mine)
+            {
+                total += cell.adj_mine_count() as usize;
+                mines += 1;
+            }
+        }
+        total as f64 / mines as f64
+    }
+
+    #[test]
+    fn clustered_mines_have_more_mined_neighbors_than_uniform_mines() {
+        let grid = Grid::init_with_distribution(20, 20, MineDistribution::Clustered);
+        assert_eq!(grid.mine_indices().len(), grid.total_mines());
+
+        assert!(
+            avg_mine_adjacency(MineDistribution::Clustered)
+                > avg_mine_adjacency(MineDistribution::Uniform)
+        );
+    }
+
+    #[test]
+    fn reveal_by_count_only_reveals_cells_with_that_count() {
+        // mines at row 0, col 2 and row 2, col 2 of a 3x5 grid
+        let mut grid = Grid::with_mines(3, 5, &[2, 12]);
+        grid.reveal_by_count(1);
+
+        for (ndx, cell) in grid.grid.iter().enumerate() {
+            let expected = [1, 3, 11, 13].contains(&ndx);
+            assert_eq!(*cell.state() == CellState::Revealed, expected);
+        }
+    }
+
+    #[test]
+    fn adjacent_indices_matches_a_brute_force_search() {
+        let mut rng = thread_rng();
+        for _ in 0..1000 {
+            let num_rows = rng.gen_range(1, 12);
+            let num_cols = rng.gen_range(1, 12);
+            let index = rng.gen_range(0, num_rows * num_cols);
+            let (r, c) = (index / num_cols, index % num_cols);
+
+            // every cell, other than the cell itself, within one row and column of it
+            let expected = (0..num_rows * num_cols)
+                .filter(|ndx| {
+                    let (nr, nc): (usize, usize) = (ndx / num_cols, ndx % num_cols);
+                    *ndx != index && nr.abs_diff(r) <= 1 && nc.abs_diff(c) <= 1
+                })
+                .collect::<Vec<usize>>();
+
+            assert_eq!(Grid::adjacent_indices(num_rows, num_cols, index), expected);
+        }
+    }
+
+    #[test]
+    fn cell_notes_survive_serialization() {
+        let mut grid = Grid::with_mines(3, 3, &[0]);
+        grid.set_note(2, 1, "hint: corner");
+
+        let json = serde_json::to_string(&grid).unwrap();
+        let grid: Grid<Cell> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(grid.get_note(2, 1), Some("hint: corner"));
+        assert_eq!(grid.get_note(0, 0), None);
+        assert_eq!(grid.mine_indices(), vec![(0, 0)]);
+    }
+
+    #[test]
+    fn reveal_random_safe_reveals_a_hidden_empty_cell() {
+        let mut grid = Grid::with_mines(3, 3, &[0, 2, 6, 8]);
+        grid.grid[4].set_state(CellState::Revealed);
+
+        let (r, c) = grid.reveal_random_safe().unwrap();
+        assert_eq!(*grid.cell(r, c).kind(), CellKind::Empty);
+        assert_eq!(*grid.cell(r, c).state(), CellState::Revealed);
+        assert_ne!((r, c), (1, 1));
+        assert_eq!(grid.free_moves(), 1);
+        assert!(!grid.is_game_lost());
+    }
+
+    #[test]
+    fn reveal_safest_is_never_a_mine_on_the_first_move() {
+        // every cell is as likely as any other to hold a mine, the top left corner is picked
+        let mut grid = Grid::from_layout("**..\n**..\n....\n....").unwrap();
+        let ((r, c), probability) = grid.reveal_safest().unwrap();
+        assert_eq!((r, c), (0, 0));
+        assert!((probability - 0.25).abs() < 1e-9);
+        assert_eq!(*grid.cell(0, 0).state(), CellState::Revealed);
+        assert!(!grid.is_game_lost());
+        assert_eq!(grid.safe_assists(), 1);
+    }
+
+    #[test]
+    fn reveal_safest_refuses_to_guess_when_the_solutions_are_cut_off() {
+        let mut grid = Grid::with_mines(2, 5, &[0, 4]);
+        grid.cell_mut(1, 0).set_state(CellState::Revealed);
+        grid.cell_mut(1, 4).set_state(CellState::Revealed);
+        grid.set_solution_limit(3);
+
+        assert_eq!(grid.reveal_safest(), None);
+        assert_eq!(grid.remaining_safe_cells(), 6);
+        assert_eq!(grid.safe_assists(), 0);
+    }
+
+    #[test]
+    fn actual_density_is_within_rounding_of_the_requested_density() {
+        for (rows, cols) in [(7, 9), (8, 8), (16, 30), (5, 3)] {
+            let grid: Grid<Cell> = Grid::init(rows, cols);
+            let half_a_mine = 0.5 / (rows * cols) as f64;
+            assert!((grid.actual_density() - 0.15).abs() <= half_a_mine);
+        }
+    }
+
+    #[test]
+    fn fairness_scores_deducible_boards_above_coin_flips() {
+        // after the opening in the top right, the two mines prove the rest of the board safe
+        let grid = Grid::from_layout("*..\n...\n..*").unwrap();
+        assert_eq!(grid.fairness(), 1.0);
+
+        // the opening leaves the mine in one of the two bottom cells
+        let grid = Grid::from_layout("..\n..\n*.").unwrap();
+        assert_eq!(grid.fairness(), 0.0);
+
+        assert_eq!(Grid::from_layout("**").unwrap().fairness(), 1.0);
+    }
+
+    #[test]
+    fn mine_distance_field_counts_king_moves_to_the_nearest_mine() {
+        let grid = Grid::from_layout("*....\n.....\n.....\n....*").unwrap();
+        assert_eq!(
+            grid.mine_distance_field(),
+            vec![
+                vec![0, 1, 2, 3, 3],
+                vec![1, 1, 2, 2, 2],
+                vec![2, 2, 2, 1, 1],
+                vec![3, 3, 2, 1, 0],
+            ]
+        );
+        assert_eq!(
+            Grid::from_layout("..").unwrap().mine_distance_field(),
+            vec![vec![usize::MAX, usize::MAX]]
+        );
+    }
+
+    #[test]
+    fn distinct_visible_numbers_lists_each_revealed_count_once() {
+        let mut grid = Grid::from_layout("**.\n*..").unwrap();
+        assert!(grid.distinct_visible_numbers().is_empty());
+
+        grid.reveal_cell(1, 1);
+        assert_eq!(grid.distinct_visible_numbers(), vec![3]);
+        grid.reveal_cell(1, 2);
+        grid.reveal_cell(0, 2);
+        assert_eq!(grid.distinct_visible_numbers(), vec![1, 3]);
+    }
+
+    #[test]
+    fn openness_is_the_share_of_empty_cells_that_are_lone() {
+        // only the three cells around the corner mine have a number
+        let grid = Grid::from_layout("*...\n....\n....").unwrap();
+        assert!((grid.openness() - 8.0 / 11.0).abs() < 1e-9);
+
+        assert_eq!(Grid::from_layout("...\n...").unwrap().openness(), 1.0);
+        assert_eq!(Grid::from_layout("*.*\n.*.").unwrap().openness(), 0.0);
+        assert_eq!(Grid::from_layout("**").unwrap().openness(), 0.0);
+    }
+
+    #[test]
+    fn density_and_mine_count_round_trip() {
+        for (rows, cols) in [(7, 9), (8, 8), (16, 30), (5, 3)] {
+            let half_a_mine = 0.5 / (rows * cols) as f32;
+            for percent in 0..=100 {
+                let density = percent as f32 / 100.0;
+                let mines = Grid::mines_for_density(rows, cols, density);
+                let round_trip = Grid::density_for_mines(rows, cols, mines);
+                assert!((round_trip - density).abs() <= half_a_mine + f32::EPSILON);
+                assert_eq!(Grid::mines_for_density(rows, cols, round_trip), mines);
+            }
+        }
+        assert_eq!(Grid::mines_for_density(3, 3, 2.0), 9);
+        assert_eq!(Grid::density_for_mines(0, 5, 3), 0.0);
+    }
+
+    #[test]
+    fn revealed_lone_cells_render_with_the_themes_blank_glyph() {
+        // mine at row 0, col 0 of a 1x3 grid
+        let mut grid = Grid::with_mines(1, 3, &[0]);
+        grid.reveal_cell(0, 2);
+        assert_eq!(format!("{}", grid), format!(" {} 1 {}\n", HIDDEN, REVEALED));
+        assert_eq!(REVEALED, ' ');
+
+        grid.set_theme(Theme {
+            revealed: '.',
+            ..Theme::default()
+        });
+        assert_eq!(format!("{}", grid), format!(" {} 1 .\n", HIDDEN));
+    }
+
+    #[test]
+    fn loaded_games_resume_the_timer_from_the_saved_elapsed_time() {
+        let mut grid = Grid::with_mines(3, 3, &[0]);
+        assert_eq!(grid.elapsed_seconds(), 0);
+        grid.reveal_cell(2, 2);
+        grid.pause_timer();
+        grid.elapsed_time = Duration::from_secs(42);
+
+        let json = serde_json::to_string(&grid).unwrap();
+        let mut grid: Grid<Cell> = serde_json::from_str(&json).unwrap();
+        grid.resume_timer();
+
+        assert!(grid.timer_start.is_some());
+        assert!((42..44).contains(&grid.elapsed_seconds()));
+    }
+
+    #[test]
+    fn open_all_zeros_reveals_every_opening_and_no_mines() {
+        let mut grid = Grid::with_mines(3, 5, &[2, 12]);
+        grid.open_all_zeros();
+
+        for (ndx, cell) in grid.grid.iter().enumerate() {
+            // only the 2 in the center doesn't border an opening
+            let expected = cell.kind() == &CellKind::Empty && ndx != 7;
+            assert_eq!(*cell.state() == CellState::Revealed, expected);
+        }
+
+        let mut grid: Grid<Cell> = Grid::init(16, 16);
+        grid.open_all_zeros();
+        assert!(!grid.is_game_lost());
+        for index in 0..grid.grid.len() {
+            if grid.grid[index].is_lone_cell() {
+                assert_eq!(*grid.grid[index].state(), CellState::Revealed);
+                for adj_ndx in Grid::adjacent_indices(16, 16, index) {
+                    assert_eq!(*grid.grid[adj_ndx].state(), CellState::Revealed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn question_marks_are_counted_and_can_be_forbidden() {
+        let mut grid = Grid::with_mines(3, 3, &[0]);
+        grid.toggle_mark(1, 1, CellMarker::Questioned);
+        grid.question_cell(2, 2);
+        assert_eq!(grid.summary().questions_used, 2);
+        // questioning a cell that is already questioned doesn't count again
+        grid.question_cell(2, 2);
+        assert_eq!(grid.summary().questions_used, 2);
+
+        let mut grid = Grid::with_mines(3, 3, &[0]);
+        grid.set_questions_enabled(false);
+        grid.question_cell(1, 1);
+        grid.toggle_mark(2, 2, CellMarker::Questioned);
+
+        assert_eq!(*grid.cell(1, 1).state(), CellState::Hidden);
+        assert_eq!(*grid.cell(2, 2).state(), CellState::Hidden);
+        assert_eq!(grid.summary().questions_used, 0);
+        assert!(format!("{}", grid.summary()).contains("question marks: forbidden"));
+
+        assert_eq!(
+            grid.try_question_cell(1, 1),
+            Err(MinesweeperError::QuestionsForbidden { r: 1, c: 1 })
+        );
+        assert_eq!(grid.summary().questions_used, 0);
+        grid.set_questions_enabled(true);
+        assert_eq!(grid.try_question_cell(1, 1), Ok(()));
+        assert_eq!(grid.summary().questions_used, 1);
     }
 
     #[test]
-    fn revealed_empty_cell_with_adjacent_mines_should_display_adj_mine_count() {
-        let cell = Cell {
-            state: CellState::Revealed,
-            kind: CellKind::Empty,
-            adj_mine_count: 3,
-        };
-        assert_eq!(format!("{}", cell), "3".to_string());
+    fn first_mine_is_the_first_mine_in_row_major_order() {
+        let grid: Grid<Cell> = Grid::init(8, 8);
+        let mut mines = grid.mine_indices();
+        mines.sort();
+        assert_eq!(grid.first_mine(), mines.first().copied());
+
+        let grid = Grid::with_mines(2, 2, &[]);
+        assert_eq!(grid.first_mine(), None);
     }
 
     #[test]
-    fn flagged_cell_displays_the_flag_char() {
-        let cell = Cell {
-            state: CellState::Marked(CellMarker::Flagged),
-            kind: CellKind::Empty,
-            adj_mine_count: 1,
-        };
-        assert_eq!(format!("{}", cell), FLAG.to_string());
+    fn layout_hash_ignores_the_state_of_play() {
+        let mut played = Grid::with_mines(4, 4, &[0, 5]);
+        played.reveal_cell(3, 3);
+        played.flag_cell(0, 0);
+        let fresh = Grid::with_mines(4, 4, &[5, 0]);
+        assert_eq!(played.layout_hash(), fresh.layout_hash());
+        // the hash is fixed, it identifies the layout across builds and toolchains
+        assert_eq!(fresh.layout_hash(), 17_786_974_010_428_172_928);
+
+        assert_ne!(
+            fresh.layout_hash(),
+            Grid::with_mines(4, 4, &[0, 6]).layout_hash()
+        );
+        assert_ne!(
+            Grid::with_mines(2, 8, &[0, 5]).layout_hash(),
+            fresh.layout_hash()
+        );
     }
 
     #[test]
-    fn questioned_cell_displays_the_question_char() {
-        let cell = Cell {
-            state: CellState::Marked(CellMarker::Questioned),
-            kind: CellKind::Empty,
-            adj_mine_count: 1,
-        };
-        assert_eq!(format!("{}", cell), QUESTION.to_string());
+    fn state_layouts_restore_the_solution_and_the_state_of_play() {
+        let grid = Grid::from_state_layout(
+            "
+            F1..
+            11.q
+            00fQ
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(grid.mine_indices(), vec![(0, 0), (2, 3)]);
+        assert!(grid.has_started());
+        let expected = format!(
+            " {f} 1 {h} {h}\n 1 1 {h} {q}\n {b} {b} {f} {q}\n",
+            f = FLAG,
+            h = HIDDEN,
+            q = QUESTION,
+            b = REVEALED
+        );
+        assert_eq!(format!("{}", grid), expected);
     }
 
     #[test]
-    fn lone_cell_test_should_return_true_for_empty_cell_with_0_adj_mines() {
-        let cell = Cell {
-            state: CellState::Marked(CellMarker::Flagged),
-            kind: CellKind::Empty,
-            adj_mine_count: 0,
-        };
-        assert!(cell.is_lone_cell());
+    fn state_layouts_reject_digits_that_do_not_match_the_mines() {
+        assert_eq!(
+            Grid::from_state_layout("*.\n21").err(),
+            Some(LayoutError::WrongCount {
+                row: 1,
+                col: 0,
+                expected: 1,
+                found: 2
+            })
+        );
+        assert_eq!(
+            Grid::from_state_layout("*.\n21").unwrap_err().to_string(),
+            "the cell at row 1 column 0 shows 2 but has 1 adjacent mines"
+        );
+        assert!(Grid::from_state_layout("*.\n11").is_ok());
     }
 
     #[test]
-    fn lone_cell_test_should_return_false_for_empty_cell_with_gt_0_adj_mines() {
-        let cell = Cell {
-            state: CellState::Marked(CellMarker::Flagged),
-            kind: CellKind::Empty,
-            adj_mine_count: 2,
-        };
-        assert_eq!(cell.is_lone_cell(), false);
+    fn layouts_reject_ragged_rows_and_unknown_characters() {
+        assert_eq!(
+            Grid::from_layout("*..\n...\n..").err(),
+            Some(LayoutError::RaggedRows {
+                row: 2,
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(
+            Grid::from_layout("*..\n.F.").err(),
+            Some(LayoutError::UnknownChar {
+                row: 1,
+                col: 1,
+                ch: 'F'
+            })
+        );
+        assert_eq!(
+            Grid::from_layout("..\n.").unwrap_err().to_string(),
+            "line 1 of the layout has 1 cells, expected 2"
+        );
+        // blank lines are skipped, but still counted when reporting the line of an error
+        assert_eq!(
+            Grid::from_layout("\n*..\n\n...\n..").err(),
+            Some(LayoutError::RaggedRows {
+                row: 4,
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(Grid::from_layout("\n \n").err(), Some(LayoutError::Empty));
+        assert_eq!(
+            Grid::from_layout("*..\n..*").unwrap().mine_indices(),
+            vec![(0, 0), (1, 2)]
+        );
     }
 
     #[test]
-    fn lone_cell_test_should_return_false_for_any_mined_cell() {
-        let cell = Cell {
-            state: CellState::Marked(CellMarker::Flagged),
-            kind: CellKind::Mine,
-            adj_mine_count: 2,
-        };
-        assert_eq!(cell.is_lone_cell(), false);
+    fn count_distribution_tallies_the_empty_cells_by_adjacent_mine_count() {
+        let grid = Grid::from_layout(
+            "
+            *..
+            ...
+            ..*
+            ",
+        )
+        .unwrap();
+        assert_eq!(grid.count_distribution(), [2, 4, 1, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(grid.count_distribution().iter().sum::<usize>(), 9 - 2);
+    }
+
+    #[test]
+    fn auto_chord_chords_the_numbers_a_reveal_opens() {
+        let layout = "
+            *.*
+            ...
+            ";
+        let mut grid = Grid::from_layout(layout).unwrap();
+        grid.flag_cell(0, 0);
+        grid.flag_cell(0, 2);
+        grid.reveal_cell(0, 1);
+        assert_eq!(*grid.cell(1, 1).state(), CellState::Hidden);
+
+        let mut grid = Grid::from_layout(layout).unwrap();
+        grid.set_auto_chord(true);
+        grid.flag_cell(0, 0);
+        grid.flag_cell(0, 2);
+        grid.reveal_cell(0, 1);
+        for c in 0..3 {
+            assert_eq!(*grid.cell(1, c).state(), CellState::Revealed);
+        }
+        assert!(!grid.is_game_lost());
+        assert_eq!(grid.clicks(), 1);
+
+        // a misplaced flag makes the auto chord detonate a mine
+        let mut grid = Grid::from_layout(layout).unwrap();
+        grid.set_auto_chord(true);
+        grid.flag_cell(0, 0);
+        grid.flag_cell(1, 0);
+        grid.reveal_cell(0, 1);
+        assert!(grid.is_game_lost());
+    }
+
+    #[test]
+    fn four_quarter_turns_return_the_original_layout() {
+        let grid = Grid::from_state_layout(
+            "
+            *f.
+            1..
+            ..*
+            0.Q
+            ",
+        )
+        .unwrap();
+
+        let rotated = grid.transform(Transform::Rotate90);
+        assert_eq!(rotated.dimensions(), (3, 4));
+        assert_eq!(rotated.mine_indices(), vec![(0, 3), (2, 0), (2, 1)]);
+        assert_eq!(
+            *rotated.cell(1, 3).state(),
+            CellState::Marked(CellMarker::Flagged)
+        );
+        assert_eq!(rotated.cell(1, 1).adj_mine_count(), 2);
+
+        let turned = rotated
+            .transform(Transform::Rotate90)
+            .transform(Transform::Rotate90)
+            .transform(Transform::Rotate90);
+        assert_eq!(turned.dimensions(), grid.dimensions());
+        assert!(turned.grid == grid.grid);
+    }
+
+    #[test]
+    fn flips_and_half_turns_mirror_the_mines() {
+        let grid = Grid::with_mines(2, 3, &[0]);
+        assert_eq!(
+            grid.transform(Transform::FlipH).mine_indices(),
+            vec![(0, 2)]
+        );
+        assert_eq!(
+            grid.transform(Transform::FlipV).mine_indices(),
+            vec![(1, 0)]
+        );
+        assert_eq!(
+            grid.transform(Transform::Rotate180).mine_indices(),
+            vec![(1, 2)]
+        );
+        assert_eq!(
+            grid.transform(Transform::Rotate270).mine_indices(),
+            vec![(2, 0)]
+        );
+    }
+
+    #[test]
+    fn render_dirty_rows_returns_only_the_changed_rows() {
+        // the middle row of mines stops the cascade from reaching the top row
+        let mut grid = Grid::from_layout("...\n***\n...\n...").unwrap();
+        assert_eq!(
+            grid.render_dirty_rows(),
+            (0..4)
+                .map(|r| (r, format!(" {} {} {}", HIDDEN, HIDDEN, HIDDEN)))
+                .collect::<Vec<(usize, String)>>()
+        );
+        assert!(grid.render_dirty_rows().is_empty());
+
+        grid.flag_cell(0, 1);
+        assert_eq!(
+            grid.render_dirty_rows(),
+            vec![(0, format!(" {} {} {}", HIDDEN, FLAG, HIDDEN))]
+        );
+
+        grid.reveal_cell(3, 0);
+        let dirty = grid.render_dirty_rows();
+        assert_eq!(
+            dirty.iter().map(|(r, _row)| *r).collect::<Vec<usize>>(),
+            vec![2, 3]
+        );
+        assert_eq!(dirty[0].1, " 2 3 2");
+    }
+
+    #[test]
+    fn region_border_mines_counts_each_bordering_mine_once() {
+        let grid = Grid::from_layout(
+            "
+            *..*
+            .*..
+            ...*
+            *...
+            ",
+        )
+        .unwrap();
+        // (1,1) borders both cells, (0,0) only the first, (0,3) and (2,3) only the second,
+        // while (3,0) borders neither
+        assert_eq!(grid.region_border_mines(&[(1, 0), (1, 2)]), 4);
+        // a mine inside the region does not border it
+        assert_eq!(grid.region_border_mines(&[(1, 1), (2, 2)]), 2);
+        assert_eq!(grid.region_border_mines(&[]), 0);
+    }
+
+    #[test]
+    fn try_methods_report_out_of_bounds_cells() {
+        let mut grid = Grid::with_mines(2, 3, &[0]);
+        match grid.try_cell(2, 1) {
+            Err(MinesweeperError::OutOfBounds { r, c, rows, cols }) => {
+                assert_eq!((r, c, rows, cols), (2, 1, 2, 3));
+            }
+            other => panic!(
+                "expected OutOfBounds, got {:?}",
+                other.map(|cell| *cell.state())
+            ),
+        }
+        assert_eq!(
+            grid.try_reveal_cell(0, 3),
+            Err(MinesweeperError::OutOfBounds {
+                r: 0,
+                c: 3,
+                rows: 2,
+                cols: 3
+            })
+        );
+        assert!(!grid.has_started());
+
+        assert_eq!(grid.try_toggle_mark(0, 0, CellMarker::Flagged), Ok(()));
+        assert!(grid.try_cell(0, 0).unwrap().is_flagged());
+        assert_eq!(grid.try_neighbors(1, 2), Ok(vec![(0, 1), (0, 2), (1, 1)]));
+        assert_eq!(
+            grid.try_chord_cell(5, 5).unwrap_err().to_string(),
+            "the cell 5,5 is outside of the 2x3 grid"
+        );
+    }
+
+    #[test]
+    fn reveal_cell_returns_the_outcome_of_the_reveal() {
+        let mut grid = Grid::from_layout("*...\n....\n....").unwrap();
+        assert_eq!(
+            grid.reveal_cell(2, 3),
+            RevealOutcome::Opening { newly_revealed: 11 }
+        );
+        assert_eq!(grid.reveal_cell(1, 1), RevealOutcome::AlreadyRevealed);
+
+        let mut grid = Grid::from_layout("*.*\n...\n*.*").unwrap();
+        assert_eq!(
+            grid.reveal_cell(1, 1),
+            RevealOutcome::Number {
+                count: 4,
+                newly_revealed: 1
+            }
+        );
+
+        grid.set_protect_questioned(true);
+        grid.question_cell(0, 0);
+        assert_eq!(grid.reveal_cell(0, 0), RevealOutcome::Protected);
+        grid.unmark_cell(0, 0);
+        assert_eq!(grid.reveal_cell(0, 0), RevealOutcome::Mine);
+        assert!(grid.is_game_lost());
+    }
+
+    #[test]
+    fn losing_on_time_is_a_loss_without_a_revealed_mine() {
+        let mut grid = Grid::from_layout("*..\n...").unwrap();
+        grid.reveal_cell(1, 2);
+        assert!(!grid.is_game_lost());
+
+        grid.lose_on_time();
+        assert!(grid.is_game_lost());
+        assert_eq!(grid.game_state(), GameState::Lost);
+        assert_eq!(grid.explain_loss(), "the time limit ran out");
+        assert!(grid.summary().lost_on_time);
+    }
+
+    #[test]
+    fn try_reveal_safe_refuses_to_reveal_a_mine() {
+        let mut grid = Grid::from_layout("*..\n...").unwrap();
+        assert_eq!(
+            grid.try_reveal_safe(0, 0),
+            Err(MinesweeperError::HitMine { r: 0, c: 0 })
+        );
+        assert_eq!(*grid.cell(0, 0).state(), CellState::Hidden);
+        assert_eq!(grid.game_state(), GameState::InProgress);
+        assert!(!grid.has_started());
+
+        // the lone cells in column 2 open their bordering numbers
+        let mut opened = grid.try_reveal_safe(0, 2).unwrap();
+        opened.sort();
+        assert_eq!(opened, vec![(0, 1), (0, 2), (1, 1), (1, 2)]);
+        assert_eq!(grid.try_reveal_safe(1, 1), Ok(vec![]));
+        assert_eq!(grid.try_reveal_safe(1, 0), Ok(vec![(1, 0)]));
+        assert_eq!(grid.game_state(), GameState::InProgress);
+    }
+
+    #[test]
+    fn a_mineless_board_is_won_by_its_first_reveal() {
+        for win_condition in [WinCondition::FlagAllMines, WinCondition::RevealAllSafe] {
+            let mut grid: Grid<Cell> = Grid::init(1, 3);
+            grid.set_win_condition(win_condition);
+            assert!(grid.is_trivial());
+            assert!(!grid.is_game_won());
+
+            grid.reveal_cell(0, 1);
+            assert!(grid.is_game_won());
+            assert!(!grid.is_game_lost());
+        }
+    }
+
+    #[test]
+    fn reveal_all_safe_wins_without_flags() {
+        let mut grid = Grid::with_mines(2, 2, &[0]);
+        grid.set_win_condition(WinCondition::RevealAllSafe);
+        grid.reveal_cell(0, 1);
+        grid.reveal_cell(1, 0);
+        assert!(!grid.is_game_won());
+        grid.reveal_cell(1, 1);
+        assert!(grid.is_game_won());
+
+        grid.set_win_condition(WinCondition::FlagAllMines);
+        assert!(!grid.is_game_won());
+    }
+
+    #[test]
+    fn revealing_the_last_safe_cell_wins_immediately() {
+        let mut grid = Grid::from_layout("*...\n....\n....").unwrap();
+        grid.set_win_condition(WinCondition::RevealAllSafe);
+        assert_eq!(grid.remaining_safe_cells(), 11);
+
+        // the cascade from the far corner reveals every empty cell
+        grid.reveal_cell(2, 3);
+        assert_eq!(grid.remaining_safe_cells(), 0);
+        assert_eq!(grid.game_state(), GameState::Won);
+        // the timer stops the moment the game is won
+        assert!(grid.has_started());
+        assert!(grid.timer_start.is_none());
+
+        // part way through a stepped cascade, revealing the rest by hand wins the game and
+        // drops the reveals that are still pending
+        let mut grid = Grid::from_layout("*....").unwrap();
+        grid.set_win_condition(WinCondition::RevealAllSafe);
+        let (_opened, more) = grid.reveal_cell_stepped(0, 4, 2);
+        assert!(more);
+        grid.reveal_cell(0, 1);
+        assert_eq!(grid.game_state(), GameState::Won);
+        assert!(grid.pending_reveals.is_empty());
+    }
+
+    #[test]
+    fn board_codes_round_trip() {
+        let grids = [
+            Grid::from_layout("*..\n...\n..*").unwrap(),
+            Grid::from_layout("......\n......").unwrap(),
+            Grid::from_layout("*******").unwrap(),
+            Grid::init_with_3bv_range(16, 30, 99, 0, 1000, 7),
+            Grid::init_symmetric(9, 9, 10, 3),
+        ];
+        for grid in grids {
+            let code = grid.to_code();
+            let decoded = Grid::from_code(&code).unwrap();
+            assert_eq!(decoded.dimensions(), grid.dimensions());
+            assert_eq!(decoded.mine_indices(), grid.mine_indices());
+            assert_eq!(decoded.to_code(), code);
+        }
+        assert_eq!(
+            Grid::from_layout("*..\n...\n..*").unwrap().to_code(),
+            "3x3.BE"
+        );
+        // the code is much shorter than the layout of a large board
+        let code = Grid::init_with_mine_count(16, 30, 99).to_code();
+        assert_eq!(code.len(), "16x30.".len() + 80);
+    }
+
+    #[test]
+    fn malformed_board_codes_are_rejected() {
+        assert_eq!(Grid::from_code("3x3BC").unwrap_err(), CodeError::Malformed);
+        assert_eq!(Grid::from_code("0x3.").unwrap_err(), CodeError::Malformed);
+        assert_eq!(
+            Grid::from_code("3x3.B").unwrap_err(),
+            CodeError::WrongLength {
+                expected: 2,
+                found: 1
+            }
+        );
+        assert_eq!(
+            Grid::from_code("3x3.B!").unwrap_err(),
+            CodeError::UnknownChar('!')
+        );
+    }
+
+    #[test]
+    fn xray_marks_hidden_mines_and_is_recorded_in_the_summary() {
+        let mut grid = Grid::with_mines(1, 3, &[0, 2]);
+        grid.flag_cell(0, 2);
+        assert_eq!(
+            grid.to_string(),
+            format!(" {} {} {}\n", HIDDEN, HIDDEN, FLAG)
+        );
+
+        grid.set_xray(true);
+        assert_eq!(grid.to_string(), format!(" {} {} {}\n", XRAY, HIDDEN, FLAG));
+        assert!(!grid.is_game_lost());
+
+        grid.set_xray(false);
+        assert_eq!(grid.glyph(0, 0), HIDDEN);
+        assert!(grid.summary().xray_used);
+        assert!(grid.summary().to_string().ends_with(", x-ray used"));
+    }
+
+    #[test]
+    fn protected_questioned_cells_are_not_revealed() {
+        let mut grid = Grid::with_mines(2, 2, &[0]);
+        grid.question_cell(1, 1);
+        grid.set_protect_questioned(true);
+        grid.reveal_cell(1, 1);
+        assert_eq!(
+            *grid.cell(1, 1).state(),
+            CellState::Marked(CellMarker::Questioned)
+        );
+        assert!(!grid.has_started());
+
+        // chording skips the protected question as well
+        grid.flag_cell(0, 0);
+        grid.reveal_cell(0, 1);
+        grid.chord_cell(0, 1);
+        assert_eq!(*grid.cell(1, 0).state(), CellState::Revealed);
+        assert_eq!(
+            *grid.cell(1, 1).state(),
+            CellState::Marked(CellMarker::Questioned)
+        );
+
+        grid.set_protect_questioned(false);
+        grid.reveal_cell(1, 1);
+        assert_eq!(*grid.cell(1, 1).state(), CellState::Revealed);
+    }
+
+    #[test]
+    fn symmetric_grids_are_invariant_under_a_half_turn() {
+        for (rows, cols, mines) in [(8, 8, 10), (5, 7, 9), (5, 7, 10), (4, 6, 7)] {
+            let grid = Grid::init_symmetric(rows, cols, mines, 42);
+            assert_eq!(
+                grid.transform(Transform::Rotate180).mine_indices(),
+                grid.mine_indices()
+            );
+            let expected = if mines % 2 == 1 && (rows * cols) % 2 == 0 {
+                mines - 1
+            } else {
+                mines
+            };
+            assert_eq!(grid.mine_indices().len(), expected);
+        }
+
+        // the odd mine of an odd sized grid sits in its center
+        assert_eq!(
+            *Grid::init_symmetric(3, 3, 1, 7).cell(1, 1).kind(),
+            CellKind::Mine
+        );
+        assert_eq!(
+            Grid::init_symmetric(6, 6, 8, 3).mine_indices(),
+            Grid::init_symmetric(6, 6, 8, 3).mine_indices()
+        );
+        assert_eq!(Grid::init_symmetric(6, 6, 8, 3).seed(), Some(3));
+        assert_eq!(Grid::from_layout("*.").unwrap().seed(), None);
+    }
+
+    #[test]
+    fn cached_neighbors_match_freshly_computed_ones() {
+        let grid: Grid<Cell> = Grid::init(7, 9);
+        let json = serde_json::to_string(&grid).unwrap();
+        let loaded: Grid<Cell> = serde_json::from_str(&json).unwrap();
+        for index in 0..7 * 9 {
+            let fresh = Grid::adjacent_indices(7, 9, index);
+            assert_eq!(grid.adjacent(index), fresh.as_slice());
+            assert_eq!(loaded.adjacent(index), fresh.as_slice());
+        }
+
+        // the cache doesn't stop a grid from being shared across threads
+        fn assert_send_sync<T: Send + Sync>(_value: &T) {}
+        assert_send_sync(&loaded);
+        std::thread::scope(|scope| {
+            scope.spawn(|| assert_eq!(loaded.adjacent(0), grid.adjacent(0)));
+        });
+    }
+
+    #[test]
+    fn total_mines_returns_the_stored_mine_count() {
+        // the default density places 15% of the cells
+        let grid: Grid<Cell> = Grid::init(10, 10);
+        assert_eq!(grid.total_mines(), grid.num_mines);
+        assert_eq!(grid.total_mines(), 15);
+        assert_eq!(grid.mine_indices().len(), 15);
+
+        let grid = Grid::init_with_mine_count(10, 10, 40);
+        assert_eq!(grid.total_mines(), grid.num_mines);
+        assert_eq!(grid.total_mines(), 40);
+        assert_eq!(grid.mine_indices().len(), 40);
+
+        assert_eq!(Grid::init_with_mine_count(2, 2, 9).total_mines(), 4);
+        assert_eq!(Grid::from_layout("*..\n.**").unwrap().total_mines(), 3);
+    }
+
+    #[test]
+    fn reachable_safe_from_matches_a_reveal_on_a_clone() {
+        let grid = Grid::from_layout(
+            "
+            ....*
+            ....*
+            **...
+            .....
+            ",
+        )
+        .unwrap();
+        for (r, c) in [(0, 0), (3, 4), (1, 3), (3, 0)] {
+            let mut clone = grid.clone();
+            clone.reveal_cell(r, c);
+            let (rows, cols) = clone.dimensions();
+            let revealed = (0..rows * cols)
+                .map(|ndx| (ndx / cols, ndx % cols))
+                .filter(|(r, c)| *clone.cell(*r, *c).state() == CellState::Revealed)
+                .collect::<Vec<(usize, usize)>>();
+            assert_eq!(grid.reachable_safe_from(r, c), revealed);
+        }
+        assert!(grid.reachable_safe_from(2, 0).is_empty());
+        assert!(!grid.has_started());
+    }
+
+    #[test]
+    fn boards_are_isomorphic_to_their_rotations_and_reflections() {
+        let grid = Grid::from_layout("**..\n...*\n....").unwrap();
+        assert!(grid.is_isomorphic(&grid));
+        for t in [
+            Transform::Rotate90,
+            Transform::Rotate180,
+            Transform::Rotate270,
+            Transform::FlipH,
+            Transform::FlipV,
+        ] {
+            assert!(grid.is_isomorphic(&grid.transform(t)));
+            assert!(grid.transform(t).is_isomorphic(&grid));
+        }
+        // the transpose is a reflection across the main diagonal
+        let transpose = Grid::from_layout("*..\n*..\n...\n.*.").unwrap();
+        assert!(grid.is_isomorphic(&transpose));
+
+        let unrelated = Grid::from_layout("*.*.\n...*\n....").unwrap();
+        assert!(!grid.is_isomorphic(&unrelated));
+        assert!(!grid.is_isomorphic(&Grid::from_layout("**..\n...*").unwrap()));
+    }
+
+    #[test]
+    fn reveal_path_stops_once_the_game_is_decided() {
+        let mut grid = Grid::from_layout("*.*\n...\n*.*").unwrap();
+        assert_eq!(grid.reveal_path(&[(0, 1), (1, 1)]), GameState::InProgress);
+        assert_eq!(grid.reveal_path(&[(2, 0), (1, 0)]), GameState::Lost);
+        // the path stopped at the mine
+        assert_eq!(*grid.cell(1, 0).state(), CellState::Hidden);
+
+        let mut grid = Grid::from_layout("*.*\n...\n*.*").unwrap();
+        grid.set_win_condition(WinCondition::RevealAllSafe);
+        let path = [(0, 1), (1, 0), (1, 1), (1, 2), (2, 1), (0, 0)];
+        assert_eq!(grid.reveal_path(&path), GameState::Won);
+        assert_eq!(*grid.cell(0, 0).state(), CellState::Hidden);
+    }
+
+    #[test]
+    fn best_opening_picks_the_largest_opening() {
+        // the small opening around row 0, col 0 is walled off from the large one on the right
+        let grid = Grid::from_layout(
+            "
+            ..*......
+            ..*......
+            ***......
+            .........
+            ",
+        )
+        .unwrap();
+        assert_eq!(grid.reachable_safe_from(0, 0).len(), 4);
+        // clicking the number at row 0, col 3 opens the lone cells next to it as well
+        let best = grid.best_opening().unwrap();
+        assert_eq!(best, (0, 3));
+        assert!(grid.reachable_safe_from(best.0, best.1).len() > 20);
+
+        assert_eq!(Grid::from_layout("**").unwrap().best_opening(), None);
+    }
+
+    #[test]
+    fn without_flags_markers_do_nothing_and_revealing_wins() {
+        let mut grid = Grid::from_layout("*..\n...").unwrap();
+        grid.set_flags_enabled(false);
+        grid.set_win_condition(WinCondition::FlagAllMines);
+        assert_eq!(grid.win_condition(), WinCondition::RevealAllSafe);
+
+        grid.flag_cell(0, 0);
+        grid.question_cell(0, 1);
+        grid.toggle_mark(0, 2, CellMarker::Flagged);
+        assert!(grid
+            .grid
+            .iter()
+            .all(|cell| *cell.state() == CellState::Hidden));
+
+        grid.reveal_cell(1, 2);
+        assert!(!grid.is_game_won());
+        grid.reveal_cell(1, 0);
+        assert!(grid.is_game_won());
+        assert!(!grid.summary().flags_enabled);
+        assert!(grid.summary().to_string().ends_with(", no flags"));
+    }
+
+    #[test]
+    fn estimated_remaining_clicks_shrinks_as_the_board_is_revealed() {
+        // one opening on the right, and three numbers on the left that do not border it
+        let mut grid = Grid::from_layout(
+            "
+            .*....
+            **....
+            ......
+            ",
+        )
+        .unwrap();
+        assert_eq!(grid.estimated_remaining_clicks(), grid.board_3bv());
+        assert_eq!(grid.estimated_remaining_clicks(), 4);
+
+        grid.reveal_cell(0, 5);
+        assert_eq!(grid.estimated_remaining_clicks(), 3);
+        grid.reveal_cell(0, 0);
+        grid.reveal_cell(2, 0);
+        assert_eq!(grid.estimated_remaining_clicks(), 1);
+        grid.reveal_cell(2, 1);
+        assert_eq!(grid.estimated_remaining_clicks(), 0);
+    }
+
+    #[test]
+    fn unflagged_mines_lists_the_mines_missed_by_the_player() {
+        let mut grid = Grid::from_layout("*.*\n...\n*.*").unwrap();
+        grid.flag_cell(0, 0);
+        grid.flag_cell(2, 2);
+        // a flag on an empty cell does not count
+        grid.flag_cell(1, 1);
+        grid.reveal_cell(0, 2);
+        assert!(grid.is_game_lost());
+        assert_eq!(grid.unflagged_mines(), vec![(0, 2), (2, 0)]);
+    }
+
+    #[test]
+    fn required_flags_shrinks_as_mines_are_flagged() {
+        let mut grid = Grid::from_layout("*.*\n...\n*.*").unwrap();
+        assert_eq!(grid.required_flags(), vec![(0, 0), (0, 2), (2, 0), (2, 2)]);
+        grid.flag_cell(0, 2);
+        grid.flag_cell(2, 0);
+        // a misplaced flag does not help
+        grid.flag_cell(1, 1);
+        assert_eq!(grid.required_flags(), vec![(0, 0), (2, 2)]);
+        grid.flag_cell(0, 0);
+        grid.flag_cell(2, 2);
+        assert!(grid.required_flags().is_empty());
+        assert!(grid.is_game_won());
+
+        grid.unmark_cell(0, 0);
+        grid.set_win_condition(WinCondition::RevealAllSafe);
+        assert!(grid.required_flags().is_empty());
+    }
+
+    #[test]
+    fn revealing_every_empty_cell_without_flags_is_won_by_reveal() {
+        // none of the empty cells are lone cells, so each reveal opens a single cell
+        let mut grid = Grid::from_layout("*.*\n...\n*.*").unwrap();
+        for (r, c) in [(0, 1), (1, 0), (1, 1), (1, 2)] {
+            grid.reveal_cell(r, c);
+        }
+        assert!(!grid.is_game_won_by_reveal());
+        grid.reveal_cell(2, 1);
+        assert!(grid.is_game_won_by_reveal());
+        assert!(grid.cells_with_marker(CellMarker::Flagged).is_empty());
+
+        // the flag based win condition still asks for the mines to be flagged
+        assert_eq!(grid.win_condition(), WinCondition::FlagAllMines);
+        assert!(!grid.is_game_won());
+        for (r, c) in [(0, 0), (0, 2), (2, 0), (2, 2)] {
+            grid.flag_cell(r, c);
+        }
+        assert!(grid.is_game_won());
+
+        grid.set_win_condition(WinCondition::RevealAllSafe);
+        grid.unmark_cell(0, 0);
+        assert!(grid.is_game_won());
+    }
+
+    #[test]
+    fn only_the_last_unflagged_mine_wins_on_flag() {
+        let mut grid = Grid::from_layout("*.*\n...\n*.*").unwrap();
+        assert!(!grid.would_win_on_flag(0, 0));
+
+        grid.flag_cell(0, 0);
+        grid.flag_cell(0, 2);
+        grid.flag_cell(2, 0);
+        grid.flag_cell(1, 1);
+        assert!(grid.would_win_on_flag(2, 2));
+        assert!(!grid.would_win_on_flag(0, 0));
+        assert!(!grid.would_win_on_flag(1, 2));
+
+        grid.flag_cell(2, 2);
+        assert!(grid.is_game_won());
+        assert!(!grid.would_win_on_flag(2, 2));
+
+        // flags are optional under RevealAllSafe, so none of them wins the game
+        grid.unmark_cell(2, 2);
+        grid.set_win_condition(WinCondition::RevealAllSafe);
+        assert!(!grid.would_win_on_flag(2, 2));
+    }
+
+    #[test]
+    fn mines_remaining_counts_down_with_every_flag() {
+        let mut grid = Grid::from_layout("*.*\n...\n*.*").unwrap();
+        assert_eq!(grid.mines_remaining(), 4);
+        grid.flag_cell(0, 0);
+        grid.flag_cell(1, 1);
+        grid.flag_cell(2, 2);
+        // questions do not count as flags
+        grid.question_cell(0, 2);
+        assert_eq!(grid.mines_remaining(), 1);
+
+        grid.flag_cell(1, 0);
+        grid.flag_cell(1, 2);
+        assert_eq!(grid.mines_remaining(), -1);
+    }
+
+    #[test]
+    fn clear_markers_keeps_the_revealed_cells() {
+        let mut grid = Grid::from_layout("*.*\n...\n*.*").unwrap();
+        grid.reveal_cell(0, 1);
+        grid.reveal_cell(1, 1);
+        grid.flag_cell(0, 0);
+        grid.flag_cell(2, 1);
+        grid.question_cell(1, 2);
+
+        grid.clear_markers();
+        assert!(grid.cells_with_marker(CellMarker::Flagged).is_empty());
+        assert!(grid.cells_with_marker(CellMarker::Questioned).is_empty());
+        assert_eq!(*grid.cell(0, 1).state(), CellState::Revealed);
+        assert_eq!(*grid.cell(1, 1).state(), CellState::Revealed);
+        assert_eq!(grid.remaining_safe_cells(), 3);
+    }
+
+    #[test]
+    fn cells_with_marker_lists_the_flags_and_the_questions() {
+        let mut grid = Grid::from_layout("*.*\n...\n*.*").unwrap();
+        assert!(grid.cells_with_marker(CellMarker::Flagged).is_empty());
+
+        grid.flag_cell(2, 2);
+        grid.flag_cell(0, 0);
+        grid.question_cell(1, 1);
+        grid.question_cell(0, 1);
+        grid.reveal_cell(1, 0);
+        // a revealed cell keeps no marker
+        grid.flag_cell(1, 0);
+        assert_eq!(
+            grid.cells_with_marker(CellMarker::Flagged),
+            vec![(0, 0), (2, 2)]
+        );
+        assert_eq!(
+            grid.cells_with_marker(CellMarker::Questioned),
+            vec![(0, 1), (1, 1)]
+        );
+    }
+
+    #[test]
+    fn correct_and_incorrect_flags_are_counted_separately() {
+        let mut grid = Grid::from_layout("*.*\n...\n*.*").unwrap();
+        assert_eq!((grid.correct_flags(), grid.incorrect_flags()), (0, 0));
+
+        grid.flag_cell(0, 0);
+        grid.flag_cell(2, 2);
+        grid.flag_cell(1, 1);
+        grid.flag_cell(1, 2);
+        grid.flag_cell(0, 1);
+        // a question mark is not a flag
+        grid.question_cell(2, 0);
+        assert_eq!((grid.correct_flags(), grid.incorrect_flags()), (2, 3));
+        assert!(grid
+            .summary()
+            .to_string()
+            .contains("flags: 2 correct, 3 incorrect, "));
+    }
+
+    #[test]
+    fn init_with_seed_reproduces_the_same_board() {
+        let grid = Grid::init_with_seed(16, 16, 42);
+        let same = Grid::init_with_seed(16, 16, 42);
+        assert_eq!(grid.mine_indices(), same.mine_indices());
+        assert!((0..16 * 16).all(|ndx| {
+            grid.cell(ndx / 16, ndx % 16).adj_mine_count()
+                == same.cell(ndx / 16, ndx % 16).adj_mine_count()
+        }));
+        assert_eq!(grid.total_mines(), Grid::mines_for_density(16, 16, 0.15));
+        assert_eq!(grid.seed(), Some(42));
+
+        assert_ne!(
+            grid.mine_indices(),
+            Grid::init_with_seed(16, 16, 43).mine_indices()
+        );
+        assert!(Grid::init(4, 4).seed().is_some());
+    }
+
+    #[test]
+    fn only_boards_init_with_seed_can_rebuild_have_a_reproducible_seed() {
+        let mut grid = Grid::init_with_seed(8, 8, 12345);
+        assert_eq!(grid.reproducible_seed(), Some(12345));
+
+        // the first reveal moves the mines around the top left corner elsewhere
+        let (r, c) = grid.mine_indices()[0];
+        grid.first_reveal(r, c);
+        assert_eq!(grid.seed(), Some(12345));
+        assert_eq!(grid.reproducible_seed(), None);
+
+        let grid = Grid::init_symmetric(4, 6, 2, 12345);
+        assert_eq!(grid.seed(), Some(12345));
+        assert_eq!(grid.reproducible_seed(), None);
+        assert_eq!(Grid::from_layout("*.").unwrap().reproducible_seed(), None);
+    }
+
+    #[test]
+    fn init_with_3bv_range_generates_a_board_in_range() {
+        let grid = Grid::init_with_3bv_range(9, 9, 10, 30, 35, 11);
+        assert!((30..=35).contains(&grid.board_3bv()));
+        assert_eq!(grid.total_mines(), 10);
+        assert_eq!(
+            grid.mine_indices(),
+            Grid::init_with_3bv_range(9, 9, 10, 30, 35, 11).mine_indices()
+        );
+
+        // a 3BV of 1 is out of reach with this many mines, the closest attempt is returned
+        let grid = Grid::init_with_3bv_range(9, 9, 30, 1, 1, 11);
+        assert!(grid.board_3bv() > 1);
+        assert_eq!(grid.total_mines(), 30);
+    }
+
+    #[test]
+    fn difficulty_label_shows_the_3bv_and_density() {
+        let grid = Grid::from_layout("*...\n....\n....\n....\n....").unwrap();
+        assert_eq!(grid.difficulty_label(), "Easy (3BV 1, 5% density)");
+
+        let grid = Grid::from_layout("*.*.\n....\n*.*.\n....").unwrap();
+        assert_eq!(
+            grid.difficulty_label(),
+            format!("Hard (3BV {}, 25% density)", grid.board_3bv())
+        );
+
+        // a dense board that opens in a single click and needs no guessing is not hard
+        let grid = Grid::from_layout("*****\n.....\n.....\n.....\n.....").unwrap();
+        assert_eq!(grid.fairness(), 1.0);
+        assert_eq!(grid.difficulty_label(), "Medium (3BV 1, 20% density)");
+    }
+
+    #[test]
+    fn stepped_reveals_open_a_cascade_in_chunks() {
+        let mut grid = Grid::with_mines(8, 8, &[63]);
+        let mut expected = grid.clone();
+        expected.reveal_cell(0, 0);
+        let expected = (0..64)
+            .map(|ndx| (ndx / 8, ndx % 8))
+            .filter(|(r, c)| *expected.cell(*r, *c).state() == CellState::Revealed)
+            .collect::<Vec<(usize, usize)>>();
+
+        let mut opened = vec![];
+        let mut steps = 0;
+        loop {
+            let (chunk, pending) = grid.reveal_cell_stepped(0, 0, 10);
+            assert!(chunk.len() <= 10);
+            opened.extend(chunk);
+            steps += 1;
+            if !pending {
+                break;
+            }
+        }
+        opened.sort();
+        assert_eq!(opened, expected);
+        assert_eq!(steps, 7);
+        assert_eq!(grid.clicks(), 1);
+        assert_eq!(*grid.cell(7, 7).state(), CellState::Hidden);
+    }
+
+    #[test]
+    fn stepped_reveals_always_make_progress_and_skip_cells_flagged_in_between() {
+        let mut grid = Grid::with_mines(8, 8, &[63]);
+        let (chunk, pending) = grid.reveal_cell_stepped(0, 0, 0);
+        assert_eq!(chunk, vec![(0, 0)]);
+        assert!(pending);
+
+        let last = *grid.pending_reveals.last().unwrap();
+        let (r, c) = (last / 8, last % 8);
+        grid.flag_cell(r, c);
+        while grid.reveal_cell_stepped(0, 0, 10).1 {}
+        assert!(grid.cell(r, c).is_flagged());
+        assert_eq!(grid.remaining_safe_cells(), 1);
+    }
+
+    #[test]
+    fn is_satisfied_needs_exactly_as_many_flags_as_adjacent_mines() {
+        let mut grid = Grid::from_state_layout("**.\n2..\n..0").unwrap();
+        grid.flag_cell(0, 0);
+        assert!(!grid.is_satisfied(1, 0));
+        grid.flag_cell(0, 1);
+        assert!(grid.is_satisfied(1, 0));
+        // a flag on the wrong cell still counts towards the number
+        grid.unmark_cell(0, 1);
+        grid.flag_cell(1, 1);
+        assert!(grid.is_satisfied(1, 0));
+
+        // hidden cells and revealed zeros are never satisfied
+        assert!(!grid.is_satisfied(0, 2));
+        assert!(!grid.is_satisfied(2, 2));
+    }
+
+    #[test]
+    fn revealed_kind_is_hidden_until_revealed_or_game_over() {
+        let mut grid = Grid::from_layout("*.*\n...\n*.*").unwrap();
+        grid.reveal_cell(0, 1);
+        assert_eq!(grid.revealed_kind(0, 1), Some(CellKind::Empty));
+        assert_eq!(grid.revealed_kind(0, 0), None);
+        assert_eq!(grid.revealed_kind(1, 1), None);
+
+        grid.reveal_cell(2, 2);
+        assert_eq!(grid.revealed_kind(0, 0), Some(CellKind::Mine));
+        assert_eq!(grid.revealed_kind(1, 1), Some(CellKind::Empty));
+    }
+
+    #[test]
+    fn to_rgba_renders_every_pixel_of_the_view() {
+        let mut grid = Grid::from_layout("*.\n..\n..").unwrap();
+        grid.reveal_cell(2, 0);
+        grid.flag_cell(0, 0);
+        let (width, height, pixels) = grid.to_rgba(4);
+        assert_eq!((width, height), (8, 12));
+        assert_eq!(pixels.len(), width * height * 4);
+
+        let pixel = |x: usize, y: usize| &pixels[(y * width + x) * 4..(y * width + x) * 4 + 4];
+        // the flag is drawn in the middle of the top left cell, on a hidden background
+        assert_eq!(pixel(0, 0), HIDDEN_RGBA);
+        assert_eq!(pixel(1, 1), FLAG_RGBA);
+        // the 1 at row 1, col 0 is drawn in blue, the lone cell at row 2, col 1 is left blank
+        assert_eq!(pixel(1, 5), NUMBER_RGBA[0]);
+        assert_eq!(pixel(5, 9), REVEALED_RGBA);
+    }
+
+    #[test]
+    fn chord_efficiency_counts_the_clicks_saved_by_chords() {
+        let mut grid = Grid::from_layout("*.*\n...\n*.*").unwrap();
+        grid.reveal_cell(0, 1);
+        grid.flag_cell(0, 0);
+        grid.flag_cell(0, 2);
+        // one chord opens the three cells of the middle row
+        grid.chord_cell(0, 1);
+        assert_eq!(grid.chord_efficiency(), 2);
+
+        // a chord that opens nothing does not count
+        grid.chord_cell(0, 1);
+        assert_eq!(grid.chord_efficiency(), 2);
     }
 }
@@ -0,0 +1,998 @@
+//! This module contains analysis functions for a minesweeper `Grid`. They only make use of
+//! the information a player can see (revealed cells and their adjacent mine counts) plus the
+//! total number of mines on the grid.
+
+use crate::mine_sweeper_board::{
+    CellKind, CellState, MineSweeperCell, MineSweeperGame, NumberStatus,
+};
+use crate::mine_sweeper_impl::{Cell, Grid};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// a revealed number on the grid, expressed as the frontier variables it touches and the number
+/// of mines that must be among them
+struct Constraint {
+    vars: Vec<usize>,
+    count: usize,
+}
+
+impl Grid<Cell> {
+    /// returns the row,col indices of every unrevealed cell that is adjacent to at least
+    /// one revealed cell, in row major order. These frontier cells are the only ones the
+    /// revealed numbers say anything about
+    pub fn frontier_cells(&self) -> Vec<(usize, usize)> {
+        let (rows, cols) = self.dimensions();
+        let mut frontier = vec![];
+        for r in 0..rows {
+            for c in 0..cols {
+                if *self.cell(r, c).state() != CellState::Revealed
+                    && self
+                        .neighbors(r, c)
+                        .iter()
+                        .any(|(nr, nc)| *self.cell(*nr, *nc).state() == CellState::Revealed)
+                {
+                    frontier.push((r, c));
+                }
+            }
+        }
+        frontier
+    }
+
+    /// builds the constraints imposed on the `frontier` cells by the revealed numbers
+    fn constraints(&self, frontier: &[(usize, usize)]) -> Vec<Constraint> {
+        let (rows, cols) = self.dimensions();
+        let mut constraints = vec![];
+        for r in 0..rows {
+            for c in 0..cols {
+                let cell = self.cell(r, c);
+                if *cell.state() != CellState::Revealed || *cell.kind() == CellKind::Mine {
+                    continue;
+                }
+                let vars = self
+                    .neighbors(r, c)
+                    .iter()
+                    .filter_map(|pos| frontier.iter().position(|f| f == pos))
+                    .collect::<Vec<usize>>();
+                if !vars.is_empty() || cell.adj_mine_count() > 0 {
+                    constraints.push(Constraint {
+                        vars,
+                        count: cell.adj_mine_count() as usize,
+                    });
+                }
+            }
+        }
+        constraints
+    }
+
+    /// returns the row,col indices of every unrevealed cell, in row major order
+    fn unrevealed(&self) -> Vec<(usize, usize)> {
+        let (rows, cols) = self.dimensions();
+        (0..rows * cols)
+            .map(|ndx| (ndx / cols, ndx % cols))
+            .filter(|(r, c)| *self.cell(*r, *c).state() != CellState::Revealed)
+            .collect()
+    }
+
+    /// splits the frontier into regions of cells that touch each other
+    fn frontier_regions(&self) -> Vec<Vec<(usize, usize)>> {
+        let frontier = self.frontier_cells();
+        let mut assigned = vec![false; frontier.len()];
+        let mut regions = vec![];
+        for start in 0..frontier.len() {
+            if assigned[start] {
+                continue;
+            }
+            assigned[start] = true;
+            let mut region = vec![];
+            let mut to_visit = vec![start];
+            while let Some(cur) = to_visit.pop() {
+                region.push(frontier[cur]);
+                for pos in self.neighbors(frontier[cur].0, frontier[cur].1) {
+                    if let Some(next) = frontier.iter().position(|f| *f == pos) {
+                        if !assigned[next] {
+                            assigned[next] = true;
+                            to_visit.push(next);
+                        }
+                    }
+                }
+            }
+            region.sort();
+            regions.push(region);
+        }
+        regions
+    }
+
+    /// pairs each frontier region with the number of flags it still needs. This is the fewest
+    /// mines the region can hold, according to the revealed numbers bordering it, minus the flags
+    /// already placed in it. A negative deficit means the region has more flags than it needs
+    pub fn region_mine_deficit(&self) -> Vec<(Vec<(usize, usize)>, i32)> {
+        let solutions = self.frontier_solutions();
+        self.frontier_regions()
+            .into_iter()
+            .map(|region| {
+                let min_mines = solutions
+                    .iter()
+                    .map(|mined| mined.iter().filter(|pos| region.contains(pos)).count())
+                    .min()
+                    .unwrap_or(0);
+                let flags = region
+                    .iter()
+                    .filter(|(r, c)| self.cell(*r, *c).is_flagged())
+                    .count();
+                let deficit = min_mines as i32 - flags as i32;
+                (region, deficit)
+            })
+            .collect()
+    }
+
+    /// labels every revealed number, in row major order, by comparing its adjacent mine count
+    /// with the number of flags around it
+    pub fn number_status(&self) -> Vec<((usize, usize), NumberStatus)> {
+        let (rows, cols) = self.dimensions();
+        (0..rows * cols)
+            .map(|ndx| (ndx / cols, ndx % cols))
+            .filter(|(r, c)| {
+                let cell = self.cell(*r, *c);
+                *cell.state() == CellState::Revealed
+                    && *cell.kind() == CellKind::Empty
+                    && cell.adj_mine_count() > 0
+            })
+            .map(|(r, c)| {
+                let flags = self
+                    .neighbors(r, c)
+                    .iter()
+                    .filter(|(nr, nc)| self.cell(*nr, *nc).is_flagged())
+                    .count();
+                let status = match flags.cmp(&(self.cell(r, c).adj_mine_count() as usize)) {
+                    Ordering::Equal => NumberStatus::Satisfied,
+                    Ordering::Less => NumberStatus::Unsatisfied,
+                    Ordering::Greater => NumberStatus::Overflagged,
+                };
+                ((r, c), status)
+            })
+            .collect()
+    }
+
+    /// returns the revealed numbers, in row major order, that the flag at `r`,`c` counts towards.
+    /// These are the numbers affected by removing the flag. Returns nothing if the cell at
+    /// `r`,`c` is not flagged
+    pub fn cells_covered_by_flag(&self, r: usize, c: usize) -> Vec<(usize, usize)> {
+        if !self.cell(r, c).is_flagged() {
+            return vec![];
+        }
+        self.neighbors(r, c)
+            .into_iter()
+            .filter(|(nr, nc)| {
+                let cell = self.cell(*nr, *nc);
+                *cell.state() == CellState::Revealed
+                    && *cell.kind() == CellKind::Empty
+                    && cell.adj_mine_count() > 0
+            })
+            .collect()
+    }
+
+    /// returns the flagged cells, in row major order, that border a revealed number whose
+    /// count is already met by its other flags. Such a flag is redundant with the number's
+    /// count: the number has more flags than mines, so at least one of them is misplaced, but
+    /// it isn't necessarily this one
+    pub fn over_flagged_cells(&self) -> Vec<(usize, usize)> {
+        let (rows, cols) = self.dimensions();
+        (0..rows * cols)
+            .map(|ndx| (ndx / cols, ndx % cols))
+            .filter(|(r, c)| self.cell(*r, *c).is_flagged())
+            .filter(|(r, c)| {
+                self.neighbors(*r, *c).iter().any(|(nr, nc)| {
+                    let number = self.cell(*nr, *nc);
+                    let other_flags = self
+                        .neighbors(*nr, *nc)
+                        .iter()
+                        .filter(|pos| **pos != (*r, *c) && self.cell(pos.0, pos.1).is_flagged())
+                        .count();
+                    *number.state() == CellState::Revealed
+                        && *number.kind() == CellKind::Empty
+                        && other_flags >= number.adj_mine_count() as usize
+                })
+            })
+            .collect()
+    }
+
+    /// enumerates every assignment of mines to the frontier cells that is consistent with the
+    /// revealed numbers and the total number of mines on the grid. Each solution is returned as
+    /// the list of frontier cells holding a mine. At most `solution_limit()` solutions are returned
+    pub fn frontier_solutions(&self) -> Vec<Vec<(usize, usize)>> {
+        self.solve(false, self.solution_limit())
+    }
+
+    /// returns the smallest and largest number of mines the frontier can hold, the rest of the
+    /// mines are spread over the unrevealed cells off of the frontier. The bounds come from the
+    /// frontier solutions. If there are none, or the solution limit cut the enumeration short,
+    /// the looser bounds given by the cell and mine counts alone are returned instead
+    pub fn frontier_mine_count_bounds(&self) -> (usize, usize) {
+        let solutions = self.frontier_solutions();
+        if solutions.is_empty() || solutions.len() >= self.solution_limit() {
+            let frontier = self.frontier_cells().len();
+            let outside = self.unrevealed().len() - frontier;
+            let total_mines = self.total_mines();
+            return (
+                total_mines.saturating_sub(outside).min(frontier),
+                total_mines.min(frontier),
+            );
+        }
+        let counts = solutions.iter().map(|mined| mined.len());
+        (counts.clone().min().unwrap_or(0), counts.max().unwrap_or(0))
+    }
+
+    /// returns `true` if there is at least one placement of mines that agrees with the revealed
+    /// numbers, the total number of mines, and treats every flagged cell as a mine
+    pub fn flags_consistent(&self) -> bool {
+        !self.solve(true, 1).is_empty()
+    }
+
+    /// enumerates up to `limit` frontier solutions. If `force_flags` is set, flagged cells must
+    /// hold a mine in every solution
+    fn solve(&self, force_flags: bool, limit: usize) -> Vec<Vec<(usize, usize)>> {
+        let frontier = self.frontier_cells();
+        let constraints = self.constraints(&frontier);
+        let unrevealed = self.unrevealed();
+        let is_forced = |(r, c): (usize, usize)| force_flags && self.cell(r, c).is_flagged();
+
+        let mut solver = Solver {
+            constraints: &constraints,
+            var_constraints: (0..frontier.len())
+                .map(|v| {
+                    (0..constraints.len())
+                        .filter(|ci| constraints[*ci].vars.contains(&v))
+                        .collect()
+                })
+                .collect(),
+            forced: frontier.iter().map(|pos| is_forced(*pos)).collect(),
+            assignment: frontier
+                .iter()
+                .map(|pos| if is_forced(*pos) { Some(true) } else { None })
+                .collect(),
+            total_mines: self.total_mines(),
+            outside_cells: unrevealed.len() - frontier.len(),
+            outside_forced: unrevealed
+                .iter()
+                .filter(|pos| !frontier.contains(pos) && is_forced(**pos))
+                .count(),
+            limit,
+            solutions: vec![],
+        };
+        if constraints.iter().all(|con| solver.is_feasible(con)) {
+            solver.search(0);
+        }
+
+        solver
+            .solutions
+            .into_iter()
+            .map(|mined| {
+                mined
+                    .iter()
+                    .enumerate()
+                    .filter(|(_v, is_mine)| **is_mine)
+                    .map(|(v, _is_mine)| frontier[v])
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// returns the unrevealed cells that hold a mine (when `is_mine` is true) or are empty (when
+    /// `is_mine` is false) in every frontier solution. Cells off of the frontier are only forced
+    /// when every solution leaves them all empty or all mined. Nothing can be proven if there are
+    /// no solutions, or if the solution limit cut the enumeration short
+    fn forced_cells(&self, is_mine: bool) -> Vec<(usize, usize)> {
+        let solutions = self.frontier_solutions();
+        if solutions.is_empty() || solutions.len() >= self.solution_limit() {
+            return vec![];
+        }
+        let frontier = self.frontier_cells();
+        let outside = self
+            .unrevealed()
+            .into_iter()
+            .filter(|pos| !frontier.contains(pos))
+            .collect::<Vec<(usize, usize)>>();
+        let total_mines = self.total_mines();
+        let outside_mines = if is_mine { outside.len() } else { 0 };
+        let outside_forced = solutions
+            .iter()
+            .all(|mined| total_mines - mined.len() == outside_mines);
+
+        let mut forced = frontier
+            .into_iter()
+            .filter(|pos| solutions.iter().all(|mined| mined.contains(pos) == is_mine))
+            .collect::<Vec<(usize, usize)>>();
+        if outside_forced {
+            forced.extend(outside);
+            forced.sort();
+        }
+        forced
+    }
+
+    /// returns the unrevealed cells that can be proven to hold a mine, using only the revealed
+    /// numbers and the total number of mines
+    pub fn deducible_mines(&self) -> Vec<(usize, usize)> {
+        self.forced_cells(true)
+    }
+
+    /// returns the pairs of frontier cells, in row major order, where every frontier solution
+    /// puts a mine in exactly one of the two and each is equally likely to hold it. Unless other
+    /// reveals tell them apart, one of the two has to be guessed on a coin flip. Nothing is
+    /// returned if the solution limit cut the enumeration short
+    pub fn fifty_fifties(&self) -> Vec<((usize, usize), (usize, usize))> {
+        let solutions = self.frontier_solutions();
+        if solutions.is_empty() || solutions.len() >= self.solution_limit() {
+            return vec![];
+        }
+        let frontier = self.frontier_cells();
+        let even = self
+            .mine_probabilities()
+            .into_iter()
+            .filter(|(pos, probability)| (probability - 0.5).abs() < 1e-9 && frontier.contains(pos))
+            .map(|(pos, _probability)| pos)
+            .collect::<Vec<(usize, usize)>>();
+
+        let mut pairs = vec![];
+        for (i, a) in even.iter().enumerate() {
+            for b in even.iter().skip(i + 1) {
+                if solutions
+                    .iter()
+                    .all(|mined| mined.contains(a) != mined.contains(b))
+                {
+                    pairs.push((*a, *b));
+                }
+            }
+        }
+        pairs
+    }
+
+    /// returns the sequence of safe reveals a solver would make, starting from the current
+    /// position. Each step reveals every cell that can be proven safe, and the cascades they open
+    /// feed the next step. The sequence stops once no unrevealed cell can be proven safe, so on a
+    /// board that can be solved without guessing, following it reveals every empty cell
+    pub fn suggested_reveal_order(&self) -> Vec<(usize, usize)> {
+        let mut board = self.clone();
+        let mut order = vec![];
+        loop {
+            let safe = board.forced_cells(false);
+            if safe.is_empty() {
+                return order;
+            }
+            for (r, c) in safe {
+                // an earlier reveal in this step may have already opened the cell
+                if *board.cell(r, c).state() != CellState::Revealed {
+                    board.reveal_cell(r, c);
+                    order.push((r, c));
+                }
+            }
+        }
+    }
+
+    /// returns `true` if the rest of the game can be won from the current position by deduction
+    /// alone, that is, following the `suggested_reveal_order` reveals every empty cell. A position
+    /// that returns `false` requires at least one guess
+    pub fn is_solvable_without_guessing(&self) -> bool {
+        if self.is_game_lost() {
+            return false;
+        }
+        let mut board = self.clone();
+        for (r, c) in self.suggested_reveal_order() {
+            board.reveal_cell(r, c);
+        }
+        // the reveals are all proven safe, so once as many cells remain as there are mines,
+        // every one of them is a mine
+        board.unrevealed().len() == board.total_mines()
+    }
+
+    /// returns the row,col indices of every unrevealed cell paired with the probability that it
+    /// holds a mine. Each frontier solution is weighted by the number of ways the remaining mines
+    /// can be spread over the unrevealed cells that are not on the frontier. The frontier is
+    /// solved one constraint region at a time, and the regions are combined by their mine counts.
+    /// Nothing is returned if there are no solutions, or if a region has `solution_limit()` or
+    /// more solutions, since a cut off enumeration would skew the probabilities
+    pub fn mine_probabilities(&self) -> Vec<((usize, usize), f64)> {
+        let unrevealed = self.unrevealed();
+        let frontier = self.frontier_cells();
+        let constraints = self.constraints(&frontier);
+        let outside_cells = unrevealed.len() - frontier.len();
+        let total_mines = self.total_mines();
+        if constraints.iter().any(|con| con.vars.len() < con.count) {
+            return vec![];
+        }
+
+        let mut regions = vec![];
+        for vars in constraint_regions(frontier.len(), &constraints) {
+            match self.region_tally(&vars, &constraints) {
+                Some(tally) if !tally.ways.is_empty() => regions.push((vars, tally)),
+                _ => return vec![],
+            }
+        }
+
+        // the relative number of ways to place the mines left over by a frontier holding `m`
+        // mines. Log weights are used, since the number of ways is huge
+        let ln_outside = (0..=frontier.len())
+            .map(|m| {
+                if m <= total_mines && total_mines - m <= outside_cells {
+                    ln_choose(outside_cells, total_mines - m)
+                } else {
+                    f64::NEG_INFINITY
+                }
+            })
+            .collect::<Vec<f64>>();
+        let max_ln = ln_outside.iter().cloned().fold(f64::MIN, f64::max);
+        let outside_weight = ln_outside
+            .iter()
+            .map(|ln_weight| (ln_weight - max_ln).exp())
+            .collect::<Vec<f64>>();
+
+        let all_ways = regions.iter().fold(vec![1.0], |ways, (_vars, tally)| {
+            convolve(&ways, &tally.ways)
+        });
+        let total_weight: f64 = all_ways
+            .iter()
+            .enumerate()
+            .map(|(m, ways)| ways * outside_weight[m])
+            .sum();
+        if total_weight == 0.0 {
+            return vec![];
+        }
+
+        let mut probabilities = vec![0.0; frontier.len()];
+        for (i, (vars, tally)) in regions.iter().enumerate() {
+            // the ways the other regions can hold each number of mines
+            let other_ways = regions
+                .iter()
+                .enumerate()
+                .filter(|(j, _region)| *j != i)
+                .fold(vec![1.0], |ways, (_j, (_vars, other))| {
+                    convolve(&ways, &other.ways)
+                });
+            for (k, mines) in tally.mines.iter().enumerate() {
+                let weight: f64 = other_ways
+                    .iter()
+                    .enumerate()
+                    .map(|(j, ways)| ways * outside_weight[k + j])
+                    .sum();
+                for (local, var) in vars.iter().enumerate() {
+                    probabilities[*var] += mines[local] * weight / total_weight;
+                }
+            }
+        }
+        let outside_probability = if outside_cells == 0 {
+            0.0
+        } else {
+            all_ways
+                .iter()
+                .enumerate()
+                .map(|(m, ways)| {
+                    ways * outside_weight[m] * total_mines.saturating_sub(m) as f64
+                        / outside_cells as f64
+                })
+                .sum::<f64>()
+                / total_weight
+        };
+
+        unrevealed
+            .into_iter()
+            .map(|pos| match frontier.iter().position(|f| *f == pos) {
+                Some(var) => (pos, probabilities[var]),
+                None => (pos, outside_probability),
+            })
+            .collect()
+    }
+
+    /// enumerates the solutions of the constraints on the frontier variables `vars`, ignoring the
+    /// other regions, and tallies them by their number of mines. Returns `None` if the
+    /// enumeration was cut short by the solution limit
+    fn region_tally(&self, vars: &[usize], constraints: &[Constraint]) -> Option<RegionTally> {
+        let region_constraints = constraints
+            .iter()
+            .filter(|con| con.vars.iter().any(|v| vars.contains(v)))
+            .map(|con| Constraint {
+                vars: con
+                    .vars
+                    .iter()
+                    .map(|v| vars.iter().position(|rv| rv == v).unwrap())
+                    .collect(),
+                count: con.count,
+            })
+            .collect::<Vec<Constraint>>();
+        let mut solver = Solver {
+            constraints: &region_constraints,
+            var_constraints: (0..vars.len())
+                .map(|v| {
+                    (0..region_constraints.len())
+                        .filter(|ci| region_constraints[*ci].vars.contains(&v))
+                        .collect()
+                })
+                .collect(),
+            forced: vec![false; vars.len()],
+            assignment: vec![None; vars.len()],
+            total_mines: self.total_mines(),
+            // the mines left over are checked once the regions are combined
+            outside_cells: self.total_mines(),
+            outside_forced: 0,
+            limit: self.solution_limit(),
+            solutions: vec![],
+        };
+        solver.search(0);
+        if solver.solutions.len() >= self.solution_limit() {
+            return None;
+        }
+
+        let mut tally = RegionTally {
+            ways: vec![],
+            mines: vec![],
+        };
+        for mined in solver.solutions {
+            let k = mined.iter().filter(|is_mine| **is_mine).count();
+            if tally.ways.len() <= k {
+                tally.ways.resize(k + 1, 0.0);
+                tally.mines.resize(k + 1, vec![0.0; vars.len()]);
+            }
+            tally.ways[k] += 1.0;
+            for (local, is_mine) in mined.iter().enumerate() {
+                if *is_mine {
+                    tally.mines[k][local] += 1.0;
+                }
+            }
+        }
+        Some(tally)
+    }
+
+    /// returns the hidden (unrevealed and unflagged) cell that is least likely to hold a mine.
+    /// Ties are broken in favor of corners, then edges, and then by row major order. Returns
+    /// `None` if `mine_probabilities` has no answer
+    pub fn safest_cell(&self) -> Option<(usize, usize)> {
+        self.safest_cell_with_probability()
+            .map(|(pos, _probability)| pos)
+    }
+
+    /// returns the `safest_cell` along with the probability that it holds a mine
+    pub fn safest_cell_with_probability(&self) -> Option<((usize, usize), f64)> {
+        let (rows, cols) = self.dimensions();
+        // corners have two sides on the edge of the grid, edges have one and the interior none
+        let border_sides = |(r, c): (usize, usize)| {
+            (r == 0 || r == rows - 1) as usize + (c == 0 || c == cols - 1) as usize
+        };
+
+        let mut safest: Option<((usize, usize), f64)> = None;
+        for (pos, probability) in self.mine_probabilities() {
+            if self.cell(pos.0, pos.1).is_flagged() {
+                continue;
+            }
+            safest = match safest {
+                Some((best, best_probability))
+                    if best_probability < probability - PROBABILITY_EPSILON
+                        || (best_probability <= probability + PROBABILITY_EPSILON
+                            && border_sides(best) >= border_sides(pos)) =>
+                {
+                    Some((best, best_probability))
+                }
+                _ => Some((pos, probability)),
+            };
+        }
+        safest
+    }
+}
+
+/// the solutions of a single constraint region, grouped by how many mines they place
+struct RegionTally {
+    // the number of solutions placing `k` mines, at index `k`
+    ways: Vec<f64>,
+    // for the solutions placing `k` mines, the number of them that put a mine in each variable
+    mines: Vec<Vec<f64>>,
+}
+
+/// splits the frontier variables `0..num_vars` into regions, where two variables share a region
+/// if a constraint links them. The regions can be solved independently of each other
+fn constraint_regions(num_vars: usize, constraints: &[Constraint]) -> Vec<Vec<usize>> {
+    let mut region_of = (0..num_vars).collect::<Vec<usize>>();
+    for con in constraints {
+        for pair in con.vars.windows(2) {
+            let (a, b) = (
+                region_root(&mut region_of, pair[0]),
+                region_root(&mut region_of, pair[1]),
+            );
+            region_of[a] = b;
+        }
+    }
+
+    let mut regions: Vec<Vec<usize>> = vec![];
+    let mut region_index = HashMap::new();
+    for v in 0..num_vars {
+        let r = region_root(&mut region_of, v);
+        let index = *region_index.entry(r).or_insert_with(|| {
+            regions.push(vec![]);
+            regions.len() - 1
+        });
+        regions[index].push(v);
+    }
+    regions
+}
+
+/// returns the variable that represents the region of `v`, shortening the paths it follows
+fn region_root(region_of: &mut [usize], mut v: usize) -> usize {
+    while region_of[v] != v {
+        region_of[v] = region_of[region_of[v]];
+        v = region_of[v];
+    }
+    v
+}
+
+/// returns the distribution of the sum of two independent counts, where `a[i]` and `b[j]` are
+/// the number of ways to get `i` and `j`
+fn convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut sum = vec![0.0; a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            sum[i + j] += x * y;
+        }
+    }
+    sum
+}
+
+/// probabilities that differ by less than this are considered equal
+const PROBABILITY_EPSILON: f64 = 1e-9;
+
+/// returns the natural log of the binomial coefficient `n` choose `k`
+fn ln_choose(n: usize, k: usize) -> f64 {
+    (1..=k)
+        .map(|i| ((n - k + i) as f64).ln() - (i as f64).ln())
+        .sum()
+}
+
+/// depth first search over the frontier variables
+struct Solver<'a> {
+    constraints: &'a [Constraint],
+    // the constraint indices each variable takes part in
+    var_constraints: Vec<Vec<usize>>,
+    // variables whose value was fixed before the search started
+    forced: Vec<bool>,
+    assignment: Vec<Option<bool>>,
+    total_mines: usize,
+    outside_cells: usize,
+    // the number of cells off of the frontier that must hold a mine
+    outside_forced: usize,
+    limit: usize,
+    solutions: Vec<Vec<bool>>,
+}
+
+impl Solver<'_> {
+    /// returns `false` if the constraint can no longer be satisfied by the current assignment
+    fn is_feasible(&self, con: &Constraint) -> bool {
+        let mines = con
+            .vars
+            .iter()
+            .filter(|v| self.assignment[**v] == Some(true))
+            .count();
+        let unassigned = con
+            .vars
+            .iter()
+            .filter(|v| self.assignment[**v].is_none())
+            .count();
+        mines <= con.count && mines + unassigned >= con.count
+    }
+
+    fn search(&mut self, var: usize) {
+        if self.solutions.len() >= self.limit {
+            return;
+        }
+        let placed = self.assignment.iter().filter(|a| **a == Some(true)).count();
+        if placed > self.total_mines {
+            return;
+        }
+        if var == self.assignment.len() {
+            // the remaining mines must fit in the cells off of the frontier
+            let remaining = self.total_mines - placed;
+            if remaining <= self.outside_cells && remaining >= self.outside_forced {
+                self.solutions
+                    .push(self.assignment.iter().map(|a| *a == Some(true)).collect());
+            }
+            return;
+        }
+
+        if self.forced[var] {
+            self.search(var + 1);
+            return;
+        }
+        for is_mine in [false, true] {
+            self.assignment[var] = Some(is_mine);
+            if self.var_constraints[var]
+                .iter()
+                .all(|ci| self.is_feasible(&self.constraints[*ci]))
+            {
+                self.search(var + 1);
+            }
+        }
+        self.assignment[var] = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mine_sweeper_board::{
+        CellKind, CellState, MineSweeperCell, MineSweeperGame, NumberStatus,
+    };
+    use crate::mine_sweeper_impl::Grid;
+
+    #[test]
+    fn frontier_solutions_enumerates_every_consistent_configuration() {
+        // a single mine at row 0, col 1 of a 2x3 grid. Revealing the bottom row leaves
+        // the counts 1 1 1, which only the middle cell of the top row can satisfy
+        let mut grid = Grid::with_mines(2, 3, &[1]);
+        for c in 0..3 {
+            grid.cell_mut(1, c).set_state(CellState::Revealed);
+        }
+
+        let mut solutions = grid.frontier_solutions();
+        solutions.sort();
+        assert_eq!(solutions, vec![vec![(0, 1)]]);
+    }
+
+    #[test]
+    fn frontier_cells_are_the_hidden_border_of_an_opening() {
+        let mut grid = Grid::from_layout("*...\n....\n....\n...*").unwrap();
+        assert!(grid.frontier_cells().is_empty());
+
+        // the opening from the top right corner reveals everything except the two mines
+        grid.reveal_cell(0, 3);
+        assert_eq!(grid.frontier_cells(), vec![(0, 0), (3, 3)]);
+
+        // a flagged cell is still unrevealed, so it stays on the frontier
+        grid.flag_cell(0, 0);
+        assert_eq!(grid.frontier_cells(), vec![(0, 0), (3, 3)]);
+    }
+
+    #[test]
+    fn frontier_solutions_respects_the_solution_limit() {
+        // mines at row 0, col 0 and row 0, col 3 of a 2x4 grid, the bottom row is revealed
+        let mut grid = Grid::with_mines(2, 4, &[0, 3]);
+        for c in 0..4 {
+            grid.cell_mut(1, c).set_state(CellState::Revealed);
+        }
+        assert_eq!(grid.frontier_solutions().len(), 1);
+
+        grid.set_solution_limit(0);
+        assert!(grid.frontier_solutions().is_empty());
+    }
+
+    #[test]
+    fn frontier_mine_count_bounds_are_tight_when_the_count_is_forced() {
+        // the revealed 1 at row 1, col 0 holds exactly one of the two mines in its three hidden
+        // neighbors, the other mine is off of the frontier
+        let mut grid = Grid::with_mines(2, 4, &[0, 7]);
+        grid.cell_mut(1, 0).set_state(CellState::Revealed);
+        assert_eq!(grid.frontier_mine_count_bounds(), (1, 1));
+
+        // without solutions, the frontier of three cells holds anywhere from none to both mines
+        grid.set_solution_limit(0);
+        assert_eq!(grid.frontier_mine_count_bounds(), (0, 2));
+    }
+
+    #[test]
+    fn region_mine_deficit_counts_the_flags_each_region_needs() {
+        // mines at row 0, col 0 and row 0, col 4 of a 2x5 grid. Revealing the bottom row leaves
+        // two separate regions in the top row, split by the revealed 0 at row 0, col 2
+        let mut grid = Grid::with_mines(2, 5, &[0, 4]);
+        for c in 0..5 {
+            grid.cell_mut(1, c).set_state(CellState::Revealed);
+        }
+        grid.cell_mut(0, 2).set_state(CellState::Revealed);
+
+        assert_eq!(
+            grid.region_mine_deficit(),
+            vec![(vec![(0, 0), (0, 1)], 1), (vec![(0, 3), (0, 4)], 1)]
+        );
+
+        grid.flag_cell(0, 0);
+        grid.flag_cell(0, 3);
+        grid.flag_cell(0, 4);
+        assert_eq!(
+            grid.region_mine_deficit(),
+            vec![(vec![(0, 0), (0, 1)], 0), (vec![(0, 3), (0, 4)], -1)]
+        );
+    }
+
+    #[test]
+    fn mine_probabilities_weight_frontier_and_outside_cells() {
+        // mines at row 0, col 0 and row 1, col 3 of a 2x4 grid. The revealed 1 at row 1, col 0
+        // has three hidden neighbors, leaving the second mine to one of the four other cells
+        let mut grid = Grid::with_mines(2, 4, &[0, 7]);
+        grid.cell_mut(1, 0).set_state(CellState::Revealed);
+
+        for ((r, c), probability) in grid.mine_probabilities() {
+            let expected = if c < 2 { 1.0 / 3.0 } else { 0.25 };
+            assert!((probability - expected).abs() < 1e-9, "{} {}", r, c);
+        }
+        // every outside cell is equally likely, the corners are preferred
+        assert_eq!(grid.safest_cell(), Some((0, 3)));
+    }
+
+    #[test]
+    fn mine_probabilities_are_not_skewed_by_the_solution_limit() {
+        // mines at row 0, col 0 and row 0, col 4 of a 2x5 grid. The revealed 1s in the bottom
+        // corners each have three hidden neighbors, forming two regions with three solutions each
+        let mut grid = Grid::with_mines(2, 5, &[0, 4]);
+        grid.cell_mut(1, 0).set_state(CellState::Revealed);
+        grid.cell_mut(1, 4).set_state(CellState::Revealed);
+
+        // the whole frontier has nine solutions, more than the limit, but each region has fewer
+        grid.set_solution_limit(4);
+        assert_eq!(grid.frontier_solutions().len(), 4);
+        let probabilities = grid.mine_probabilities();
+        assert_eq!(probabilities.len(), 8);
+        for ((r, c), probability) in probabilities {
+            let expected = if c == 2 { 0.0 } else { 1.0 / 3.0 };
+            assert!((probability - expected).abs() < 1e-9, "{} {}", r, c);
+        }
+
+        // once a region reaches the limit, no probabilities are given rather than skewed ones
+        grid.set_solution_limit(3);
+        assert!(grid.mine_probabilities().is_empty());
+        assert_eq!(grid.safest_cell(), None);
+    }
+
+    #[test]
+    fn safest_cell_picks_the_frontier_cell_that_cannot_be_a_mine() {
+        // mines at row 0, col 0 and row 0, col 2 of a 2x3 grid. With the bottom corners
+        // and the bottom middle revealed, the two mines must be in the top corners
+        let mut grid = Grid::with_mines(2, 3, &[0, 2]);
+        grid.cell_mut(1, 0).set_state(CellState::Revealed);
+        grid.cell_mut(1, 2).set_state(CellState::Revealed);
+        grid.cell_mut(1, 1).set_state(CellState::Revealed);
+
+        assert_eq!(grid.safest_cell(), Some((0, 1)));
+    }
+
+    #[test]
+    fn deducible_mines_lists_forced_mines_only() {
+        let mut grid = Grid::with_mines(2, 3, &[0, 2]);
+        for c in 0..3 {
+            grid.cell_mut(1, c).set_state(CellState::Revealed);
+        }
+        assert_eq!(grid.deducible_mines(), vec![(0, 0), (0, 2)]);
+
+        // the mine next to the revealed 1 could be in any of its three hidden neighbors
+        let mut grid = Grid::with_mines(2, 4, &[0, 7]);
+        grid.cell_mut(1, 0).set_state(CellState::Revealed);
+        assert!(grid.deducible_mines().is_empty());
+    }
+
+    #[test]
+    fn flags_consistent_detects_contradictory_flags() {
+        let mut grid = Grid::with_mines(2, 4, &[0, 7]);
+        grid.cell_mut(1, 0).set_state(CellState::Revealed);
+        assert!(grid.flags_consistent());
+
+        // a single flag next to the revealed 1 is fine, even if it is misplaced
+        grid.flag_cell(1, 1);
+        assert!(grid.flags_consistent());
+
+        // two flags next to the revealed 1 contradict it
+        grid.flag_cell(0, 1);
+        assert!(!grid.flags_consistent());
+
+        // more flags than mines can never be consistent
+        grid.unmark_cell(0, 1);
+        grid.flag_cell(0, 2);
+        grid.flag_cell(0, 3);
+        assert!(!grid.flags_consistent());
+    }
+
+    #[test]
+    fn following_the_suggested_reveal_order_solves_a_no_guess_board() {
+        // with the top right corner opened, each revealed 1 has one mine among its hidden
+        // neighbors, and the total of two mines proves the bottom left corner is safe
+        let mut grid = Grid::from_layout("*..\n...\n..*").unwrap();
+        for (r, c) in [(0, 1), (0, 2), (1, 1), (1, 2)] {
+            grid.cell_mut(r, c).set_state(CellState::Revealed);
+        }
+
+        let order = grid.suggested_reveal_order();
+        assert_eq!(order, vec![(2, 0)]);
+        for (r, c) in order {
+            assert_ne!(*grid.cell(r, c).kind(), CellKind::Mine);
+            grid.reveal_cell(r, c);
+            assert!(!grid.is_game_lost());
+        }
+        for (r, c) in grid.deducible_mines() {
+            grid.flag_cell(r, c);
+        }
+        assert!(grid.is_game_won());
+    }
+
+    #[test]
+    fn is_solvable_without_guessing_tells_deducible_positions_from_guesses() {
+        let mut grid = Grid::from_layout("*..\n...\n..*").unwrap();
+        for (r, c) in [(0, 1), (0, 2), (1, 1), (1, 2)] {
+            grid.cell_mut(r, c).set_state(CellState::Revealed);
+        }
+        assert!(grid.is_solvable_without_guessing());
+        // checking the position does not change it
+        assert_eq!(*grid.cell(2, 0).state(), CellState::Hidden);
+
+        // the mine next to the revealed 1 could be in any of its three hidden neighbors
+        let mut grid = Grid::with_mines(2, 4, &[0, 7]);
+        grid.cell_mut(1, 0).set_state(CellState::Revealed);
+        assert!(!grid.is_solvable_without_guessing());
+    }
+
+    #[test]
+    fn fifty_fifties_finds_pairs_that_can_only_be_guessed() {
+        // the two 1s in the middle row share the same two hidden neighbors, one of which holds
+        // the only mine
+        let grid = Grid::from_state_layout("00\n11\n*.").unwrap();
+        assert_eq!(grid.fifty_fifties(), vec![((2, 0), (2, 1))]);
+
+        // until the bottom left is revealed, the hidden neighbors of each revealed 1 look like a
+        // coin flip. The 0 found there proves one cell of each pair safe
+        let mut grid = Grid::from_layout("*..\n...\n..*").unwrap();
+        for (r, c) in [(0, 1), (0, 2), (1, 1), (1, 2)] {
+            grid.cell_mut(r, c).set_state(CellState::Revealed);
+        }
+        assert_eq!(
+            grid.fifty_fifties(),
+            vec![((0, 0), (1, 0)), ((2, 1), (2, 2))]
+        );
+        grid.cell_mut(2, 0).set_state(CellState::Revealed);
+        assert!(grid.fifty_fifties().is_empty());
+    }
+
+    #[test]
+    fn over_flagged_cells_reports_flags_next_to_satisfied_numbers() {
+        // the revealed 1 at row 1, col 1 only borders the mine at row 0, col 0
+        let mut grid = Grid::from_state_layout("*..\n.1.\n...").unwrap();
+        grid.flag_cell(0, 0);
+        assert!(grid.over_flagged_cells().is_empty());
+
+        // the 1 now has two flags, the correct flag on the mine is reported along with the
+        // misplaced one, since the number can't tell which of them is wrong
+        grid.flag_cell(2, 2);
+        assert_eq!(grid.over_flagged_cells(), vec![(0, 0), (2, 2)]);
+
+        grid.unmark_cell(0, 0);
+        assert!(grid.over_flagged_cells().is_empty());
+    }
+
+    #[test]
+    fn cells_covered_by_flag_lists_the_numbers_bordering_the_flag() {
+        let mut grid = Grid::from_state_layout("1*1\n...").unwrap();
+        assert!(grid.cells_covered_by_flag(0, 1).is_empty());
+
+        grid.flag_cell(0, 1);
+        assert_eq!(grid.cells_covered_by_flag(0, 1), vec![(0, 0), (0, 2)]);
+
+        // a misplaced flag counts towards the numbers around it just the same
+        grid.flag_cell(1, 0);
+        assert_eq!(grid.cells_covered_by_flag(1, 0), vec![(0, 0)]);
+    }
+
+    #[test]
+    fn number_status_compares_flags_with_counts() {
+        // the three revealed 1s each border the mine at row 0, col 1
+        let mut grid = Grid::from_state_layout(".*.\n111\n...").unwrap();
+        grid.flag_cell(0, 1);
+        grid.flag_cell(2, 0);
+        assert_eq!(
+            grid.number_status(),
+            vec![
+                ((1, 0), NumberStatus::Overflagged),
+                ((1, 1), NumberStatus::Overflagged),
+                ((1, 2), NumberStatus::Satisfied)
+            ]
+        );
+
+        grid.unmark_cell(0, 1);
+        grid.unmark_cell(2, 0);
+        grid.flag_cell(0, 2);
+        assert_eq!(
+            grid.number_status(),
+            vec![
+                ((1, 0), NumberStatus::Unsatisfied),
+                ((1, 1), NumberStatus::Satisfied),
+                ((1, 2), NumberStatus::Satisfied)
+            ]
+        );
+    }
+}